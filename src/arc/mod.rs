@@ -1,4 +1,10 @@
-use std::{ops::Deref, ptr::NonNull, sync::atomic::AtomicUsize};
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::Deref,
+    ptr::NonNull,
+    sync::atomic::AtomicUsize,
+};
 
 use crate::r#box::Box;
 
@@ -26,6 +32,43 @@ impl<T> Arc<T> {
             phantom: std::marker::PhantomData,
         }
     }
+
+    /// Whether both `Arc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// A pointer to the data, valid for as long as some `Arc` to it lives.
+    pub fn as_ptr(this: &Self) -> *const T {
+        // Derived from the whole `ArcInner` without going through a
+        // reference, so `from_raw` may step back to the count from it.
+        unsafe { &raw const (*this.ptr.as_ptr()).data }
+    }
+
+    /// Gives up this `Arc` without decrementing the count, e.g. to pass the
+    /// data through C code as a `void *`. Call [`Arc::from_raw`] on the
+    /// pointer to get the `Arc` back, or the data leaks.
+    pub fn into_raw(this: Self) -> *const T {
+        let ptr = Self::as_ptr(&this);
+        std::mem::forget(this);
+        ptr
+    }
+
+    /// Takes back an `Arc` given up by [`Arc::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Arc::<T>::into_raw`, with this exact `T`, and
+    /// each `into_raw` may be matched by only one `from_raw`.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        let offset = std::mem::offset_of!(ArcInner<T>, data);
+        let inner = unsafe { ptr.byte_sub(offset) }.cast::<ArcInner<T>>();
+        Arc {
+            // SAFETY: it came from a `NonNull` in `into_raw`.
+            ptr: unsafe { NonNull::new_unchecked(inner.cast_mut()) },
+            phantom: std::marker::PhantomData,
+        }
+    }
 }
 
 unsafe impl<T: Send + Sync> Send for Arc<T> {}
@@ -40,6 +83,46 @@ impl<T> Deref for Arc<T> {
     }
 }
 
+impl<T: Debug> Debug for Arc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for Arc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+// Comparisons and hashing use the pointed-to value; `ptr_eq` compares
+// identity.
+impl<T: PartialEq> PartialEq for Arc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Arc<T> {}
+
+impl<T: PartialOrd> PartialOrd for Arc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord> Ord for Arc<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: Hash> Hash for Arc<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
 impl<T> Clone for Arc<T> {
     fn clone(&self) -> Self {
         let inner = unsafe { self.ptr.as_ref() };
@@ -64,8 +147,11 @@ impl<T> Drop for Arc<T> {
             return;
         }
 
-        // std::sync::atomic::fence(std::sync::atomic::Ordering::Acquire);
-        inner.rc.load(std::sync::atomic::Ordering::Acquire);
+        // Every other owner's `fetch_sub` was a Release, ordering its uses of
+        // the data before its decrement. This fence acquires all of those
+        // decrements at once, so no use of the data can happen after (or
+        // race with) the deallocation below. Only the last owner pays for it.
+        std::sync::atomic::fence(std::sync::atomic::Ordering::Acquire);
         // If the reference count reaches zero, we can safely deallocate the memory
         unsafe {
             // Convert the pointer back to Box to deallocate
@@ -78,6 +164,37 @@ impl<T> Drop for Arc<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn arc_raw_round_trip() {
+        let arc = Arc::new(String::from("ffi"));
+        let other = arc.clone();
+        assert_eq!(Arc::as_ptr(&arc), &*other as *const String);
+
+        let ptr = Arc::into_raw(arc);
+        // The count stays up while the pointer is out.
+        assert_eq!(unsafe { &*ptr }, "ffi");
+        drop(other);
+        assert_eq!(unsafe { &*ptr }, "ffi");
+
+        let back = unsafe { Arc::from_raw(ptr) };
+        assert_eq!(*back, "ffi");
+        assert_eq!(Arc::as_ptr(&back), ptr);
+        let clone = back.clone();
+        assert!(Arc::ptr_eq(&back, &clone));
+    }
+
+    #[test]
+    fn arc_raw_through_void_pointer() {
+        extern "C" fn callback(data: *const std::ffi::c_void) -> u64 {
+            let arc = unsafe { Arc::from_raw(data.cast::<(u8, u64)>()) };
+            arc.1
+        }
+
+        // Through an untyped pointer, as a C callback's user data would be.
+        let arc = Arc::new((1u8, 7u64));
+        assert_eq!(callback(Arc::into_raw(arc).cast()), 7);
+    }
+
     #[test]
     fn arc_creation() {
         let arc = Arc::new(42);
@@ -140,4 +257,26 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn arc_display() {
+        let arc = Arc::new(1.5);
+        assert_eq!(format!("{arc} {:.2}", arc.clone()), "1.5 1.50");
+    }
+
+    #[test]
+    fn arc_value_comparisons() {
+        use std::collections::HashMap;
+
+        let a = Arc::new(String::from("key"));
+        let b = Arc::new(String::from("key"));
+        assert_eq!(a, b);
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert!(Arc::ptr_eq(&a, &a.clone()));
+        assert!(Arc::new(1) < Arc::new(2));
+
+        let mut map = HashMap::new();
+        map.insert(a, 1);
+        assert_eq!(map.get(&b), Some(&1));
+    }
 }
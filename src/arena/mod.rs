@@ -0,0 +1,177 @@
+use std::{
+    alloc::Layout,
+    cell::{Cell, RefCell},
+    ptr::{self, NonNull},
+};
+
+use crate::vec::Vec;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// A bump allocator: every allocation is a pointer increment inside the
+/// current chunk, and all memory is released at once when the arena drops.
+///
+/// Values placed in the arena are never dropped, only their memory is
+/// reclaimed, so anything owning resources (a `String`, a file) leaks them.
+/// Chunks double in size, so the number of chunks grows logarithmically with
+/// the bytes allocated.
+pub struct Arena {
+    chunks: RefCell<Vec<(*mut u8, Layout)>>,
+    current: Cell<*mut u8>,
+    remaining: Cell<usize>,
+}
+
+// Moving the arena to another thread is fine even for `!Send` values: every
+// reference handed out borrows the arena, so none can be live across the
+// move, and since destructors never run the values are never touched again.
+// The `Cell`s keep `Arena` `!Sync`.
+unsafe impl Send for Arena {}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            current: Cell::new(ptr::null_mut()),
+            remaining: Cell::new(0),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        unsafe {
+            ptr.write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if layout.size() == 0 {
+            return unsafe { NonNull::new_unchecked(layout.align() as *mut u8) };
+        }
+        if let Some(ptr) = self.bump(layout) {
+            return ptr;
+        }
+        self.new_chunk(layout);
+        self.bump(layout).expect("fresh chunk fits the layout")
+    }
+
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let current = self.current.get();
+        if current.is_null() {
+            return None;
+        }
+        let padding = current.align_offset(layout.align());
+        let needed = padding.checked_add(layout.size())?;
+        if needed > self.remaining.get() {
+            return None;
+        }
+        let ptr = unsafe { current.add(padding) };
+        self.current.set(unsafe { ptr.add(layout.size()) });
+        self.remaining.set(self.remaining.get() - needed);
+        NonNull::new(ptr)
+    }
+
+    fn new_chunk(&self, layout: Layout) {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.last().map_or(CHUNK_SIZE / 2, |(_, l)| l.size());
+        // Room for the value even when the chunk start needs padding.
+        let size = (last * 2).max(layout.size() + layout.align());
+        let chunk_layout =
+            Layout::from_size_align(size, layout.align().max(16)).expect("arena chunk too large");
+        let ptr = unsafe { std::alloc::alloc(chunk_layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(chunk_layout);
+        }
+        chunks.push((ptr, chunk_layout));
+        self.current.set(ptr);
+        self.remaining.set(size);
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for &(ptr, layout) in self.chunks.get_mut().iter() {
+            unsafe { std::alloc::dealloc(ptr, layout) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc() {
+        let arena = Arena::new();
+        let a = arena.alloc(1u8);
+        let b = arena.alloc(2u64);
+        let c = arena.alloc([3u16; 3]);
+        *a += 10;
+        assert_eq!((*a, *b, *c), (11, 2, [3; 3]));
+        assert_eq!(b as *mut u64 as usize % std::mem::align_of::<u64>(), 0);
+        assert_eq!(arena.chunks.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_many_small_allocations() {
+        #[derive(Debug, PartialEq)]
+        struct Point {
+            x: u32,
+            y: u32,
+            z: u32,
+        }
+
+        let arena = Arena::new();
+        let mut points = std::vec::Vec::new();
+        for i in 0..10_000 {
+            points.push(arena.alloc(Point {
+                x: i,
+                y: i * 2,
+                z: i * 3,
+            }));
+        }
+        for (i, p) in points.iter().enumerate() {
+            let i = i as u32;
+            assert_eq!(
+                **p,
+                Point {
+                    x: i,
+                    y: i * 2,
+                    z: i * 3
+                }
+            );
+        }
+
+        // 120 KB of points in doubling chunks starting at 4 KB.
+        assert!(arena.chunks.borrow().len() <= 6);
+    }
+
+    #[test]
+    fn test_large_and_zero_sized() {
+        let arena = Arena::new();
+        let big = arena.alloc([7u8; CHUNK_SIZE * 3]);
+        assert!(big.iter().all(|&b| b == 7));
+        arena.alloc(());
+        let small = arena.alloc(1u32);
+        assert_eq!(*small, 1);
+    }
+
+    #[test]
+    fn test_over_aligned() {
+        #[repr(align(256))]
+        struct Aligned(u8);
+
+        let arena = Arena::new();
+        arena.alloc(1u8);
+        let a = arena.alloc(Aligned(5));
+        assert_eq!(a as *mut Aligned as usize % 256, 0);
+        assert_eq!(a.0, 5);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{cond_var::Condvar, mutex::Mutex};
+
+/// Returned by [`Barrier::wait`]; exactly one thread per generation is the
+/// leader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+struct BarrierState {
+    // Threads that have arrived in the current generation.
+    count: usize,
+    generation: u64,
+}
+
+/// Blocks `n` threads until all of them have called `wait`, then releases
+/// them together. The barrier can be reused right away for the next round.
+pub struct Barrier {
+    state: Mutex<BarrierState>,
+    condvar: Condvar,
+    n: usize,
+}
+
+impl Barrier {
+    pub const fn new(n: usize) -> Self {
+        Self {
+            state: Mutex::new(BarrierState {
+                count: 0,
+                generation: 0,
+            }),
+            condvar: Condvar::new(),
+            n,
+        }
+    }
+
+    /// Blocks until `n` threads have reached the barrier. The last one to
+    /// arrive is the leader and wakes the others.
+    ///
+    /// A barrier of size 0 or 1 never blocks, and every caller is a leader.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let mut state = self.state.lock();
+        state.count += 1;
+        if state.count < self.n {
+            // Waiting on the generation rather than on `count` keeps a woken
+            // thread from sleeping again if a fast thread has already
+            // re-entered the next round and bumped `count`.
+            let generation = state.generation;
+            while state.generation == generation {
+                state = self.condvar.wait(state);
+            }
+            BarrierWaitResult(false)
+        } else {
+            state.count = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.condvar.notify_all();
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_leader_per_generation() {
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 100;
+
+        let barrier = Barrier::new(THREADS);
+        let leaders: std::vec::Vec<AtomicUsize> =
+            (0..ROUNDS).map(|_| AtomicUsize::new(0)).collect();
+        // Every thread bumps this before crossing round `r`, so once across,
+        // all `THREADS * (r + 1)` increments must already be visible.
+        let arrived = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    for (round, leader) in leaders.iter().enumerate() {
+                        arrived.fetch_add(1, Ordering::Relaxed);
+                        if barrier.wait().is_leader() {
+                            leader.fetch_add(1, Ordering::Relaxed);
+                        }
+                        assert!(arrived.load(Ordering::Relaxed) >= THREADS * (round + 1));
+                    }
+                });
+            }
+        });
+
+        assert!(leaders.iter().all(|l| l.load(Ordering::Relaxed) == 1));
+        assert_eq!(arrived.load(Ordering::Relaxed), THREADS * ROUNDS);
+    }
+
+    #[test]
+    fn test_single_thread_barrier() {
+        let barrier = Barrier::new(1);
+        assert!(barrier.wait().is_leader());
+        assert!(barrier.wait().is_leader());
+    }
+}
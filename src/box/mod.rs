@@ -1,6 +1,6 @@
 use std::{
     alloc::{Layout, handle_alloc_error},
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
@@ -137,6 +137,12 @@ impl<T: Debug + ?Sized> Debug for Box<T> {
     }
 }
 
+impl<T: Display + ?Sized> Display for Box<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&**self, f)
+    }
+}
+
 /// ```
 /// use learn_unsafe::r#box::Box;
 /// let mut a = 42;
@@ -429,4 +435,14 @@ mod test {
         boxed_dyn.do_something();
         // The drop will happen automatically at the end of this scope
     }
+
+    #[test]
+    fn test_display() {
+        let a = Box::new(42);
+        assert_eq!(format!("{a} {a:04}"), "42 0042");
+
+        let b: Box<dyn std::fmt::Display> =
+            unsafe { Box::from_raw(Box::into_raw(Box::new("dyn")) as *mut dyn std::fmt::Display) };
+        assert_eq!(b.to_string(), "dyn");
+    }
 }
@@ -1,9 +1,30 @@
-use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::{
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 
 use atomic_wait::{wait, wake_all, wake_one};
 
 use crate::mutex::MutexGuard;
 
+/// Whether a timed wait returned because its timeout elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// A condition variable for the crate's futex [`Mutex`](crate::mutex::Mutex).
+///
+/// `counter` is bumped on every notification that finds a waiter, and a
+/// waiter sleeps until it differs from the value it saw before unlocking.
+/// The bump wraps at `u32::MAX` (`fetch_add` on atomics always wraps), which
+/// is harmless: the futex only compares for equality, so a waiter can only be
+/// fooled if exactly 2^32 notifications happen between its load and its
+/// sleep, while it still has not reached the futex call.
 pub struct Condvar {
     counter: AtomicU32,
     waiter: AtomicUsize,
@@ -17,9 +38,35 @@ impl Condvar {
         }
     }
 
-    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+    /// The number of threads currently inside a wait (a racy snapshot, for
+    /// diagnostics).
+    pub fn waiters(&self) -> usize {
+        self.waiter.load(Ordering::Relaxed)
+    }
+
+    // Lost wakeups are ruled out by doing both of these *before* the guard
+    // is dropped. A notifier that changes the condition has to lock the
+    // mutex to do so, which happens after our unlock, so its Acquire makes
+    // our `waiter` increment visible: it cannot see 0 and skip the wake. Its
+    // `counter` bump is likewise ordered after our load, so if it lands
+    // before we reach `wait`, the futex sees a changed value and returns
+    // immediately. Relaxed is enough because the mutex provides the
+    // happens-before edges; notifying without having changed the condition
+    // under the mutex can lose a wakeup with any condvar.
+    fn register(&self) -> u32 {
         let counter = self.counter.load(Ordering::Relaxed);
         self.waiter.fetch_add(1, Ordering::Relaxed);
+        counter
+    }
+
+    /// Releases the mutex, sleeps until notified (or spuriously woken) and
+    /// locks it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex was poisoned while this thread was waiting.
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        let counter = self.register();
 
         let mutex = guard.lock;
         drop(guard);
@@ -30,20 +77,154 @@ impl Condvar {
         mutex.lock()
     }
 
-    pub fn notify_one(&self) {
+    /// Like [`wait`](Self::wait), but gives up after `dur`. Wakeups may
+    /// still be spurious, so callers usually want
+    /// [`wait_timeout_while`](Self::wait_timeout_while).
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        dur: Duration,
+    ) -> (MutexGuard<'a, T>, WaitTimeoutResult) {
+        let counter = self.register();
+
+        let mutex = guard.lock;
+        drop(guard);
+
+        let timed_out = futex::wait_timeout(&self.counter, counter, dur);
+        self.waiter.fetch_sub(1, Ordering::Relaxed);
+
+        (mutex.lock(), WaitTimeoutResult(timed_out))
+    }
+
+    /// Waits while `condition` holds, for at most `dur` in total.
+    ///
+    /// The remaining time is recomputed from a fixed deadline after every
+    /// wakeup, so spurious or unrelated notifications never extend the wait.
+    /// Times out only if `condition` is still true at the deadline.
+    pub fn wait_timeout_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        dur: Duration,
+        mut condition: F,
+    ) -> (MutexGuard<'a, T>, WaitTimeoutResult)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        // A deadline past what `Instant` can represent is as good as never.
+        let deadline = Instant::now().checked_add(dur);
+        loop {
+            if !condition(&mut *guard) {
+                return (guard, WaitTimeoutResult(false));
+            }
+            let Some(deadline) = deadline else {
+                guard = self.wait(guard);
+                continue;
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return (guard, WaitTimeoutResult(true));
+            }
+            guard = self.wait_timeout(guard, remaining).0;
+        }
+    }
+
+    /// Wakes one waiter, returning whether any thread was waiting.
+    ///
+    /// The answer is a snapshot of the waiter count: a waiter that is just
+    /// timing out still counts, so `true` means a wake was issued, not that a
+    /// thread is guaranteed to consume it.
+    pub fn notify_one(&self) -> bool {
         if self.waiter.load(Ordering::Relaxed) == 0 {
-            return;
+            return false;
         }
         self.counter.fetch_add(1, Ordering::Relaxed);
         wake_one(&self.counter);
+        true
     }
 
-    pub fn notify_all(&self) {
-        if self.waiter.load(Ordering::Relaxed) == 0 {
-            return;
+    /// Wakes all waiters, returning how many were waiting (a snapshot, as
+    /// for [`notify_one`](Self::notify_one)).
+    pub fn notify_all(&self) -> usize {
+        let waiters = self.waiter.load(Ordering::Relaxed);
+        if waiters == 0 {
+            return 0;
         }
         self.counter.fetch_add(1, Ordering::Relaxed);
         wake_all(&self.counter);
+        waiters
+    }
+}
+
+// `atomic_wait` has no timed wait, so call the futex directly.
+#[cfg(target_os = "linux")]
+mod futex {
+    use std::{sync::atomic::AtomicU32, time::Duration};
+
+    /// Sleeps while `atomic == value`, for at most `timeout` (relative).
+    /// Returns whether the timeout expired.
+    pub fn wait_timeout(atomic: &AtomicU32, value: u32, timeout: Duration) -> bool {
+        let timeout = libc::timespec {
+            tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        };
+        let r = unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                atomic.as_ptr(),
+                libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+                value,
+                &timeout as *const libc::timespec,
+            )
+        };
+        r == -1 && std::io::Error::last_os_error().raw_os_error() == Some(libc::ETIMEDOUT)
+    }
+}
+
+// Elsewhere, poll the counter with a growing sleep until it changes or the
+// deadline passes. Notifications still bump the counter, so none are lost;
+// they are just noticed up to a millisecond late.
+#[cfg(not(target_os = "linux"))]
+mod futex {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::{Duration, Instant},
+    };
+
+    /// Sleeps while `atomic == value`, for at most `timeout` (relative).
+    /// Returns whether the timeout expired.
+    pub fn wait_timeout(atomic: &AtomicU32, value: u32, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut backoff = Duration::from_micros(1);
+        while atomic.load(Ordering::Relaxed) == value {
+            let now = Instant::now();
+            let sleep = match deadline {
+                Some(deadline) if now >= deadline => return true,
+                Some(deadline) => backoff.min(deadline - now),
+                None => backoff,
+            };
+            std::thread::sleep(sleep);
+            backoff = (backoff * 2).min(Duration::from_millis(1));
+        }
+        false
+    }
+}
+
+impl std::fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Condvar")
+            .field("counter", &self.counter.load(Ordering::Relaxed))
+            .field("waiters", &self.waiters())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+impl Condvar {
+    fn with_counter(counter: u32) -> Self {
+        Self {
+            counter: AtomicU32::new(counter),
+            waiter: AtomicUsize::new(0),
+        }
     }
 }
 
@@ -58,11 +239,44 @@ mod tests {
     use super::*;
     use crate::mutex::Mutex;
     use std::collections::VecDeque;
+    use std::sync::atomic::AtomicBool;
 
     #[test]
     fn test_condvar() {
+        producer_consumer(Condvar::new());
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let condvar = Condvar::with_counter(u32::MAX - 3);
+        assert!(!condvar.notify_one());
+        let condvar = producer_consumer(condvar);
+        // Depending on timing the counter may not have been bumped at all, but
+        // normally it crosses zero; either way nobody hung.
+        assert_eq!(condvar.waiters(), 0);
+
+        let condvar = Condvar::with_counter(u32::MAX);
+        let ready = Mutex::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let mut r = ready.lock();
+                while !*r {
+                    r = condvar.wait(r);
+                }
+            });
+            while condvar.waiters() == 0 {
+                std::thread::yield_now();
+            }
+            *ready.lock() = true;
+            assert!(condvar.notify_one());
+        });
+        assert_eq!(condvar.counter.load(Ordering::Relaxed), 0);
+    }
+
+    fn producer_consumer(not_empty: Condvar) -> Condvar {
         let queue = Mutex::new(VecDeque::new());
-        let not_empty = Condvar::new();
+        let mut waits = 0;
+        let mut notified = 0;
 
         std::thread::scope(|s| {
             s.spawn(|| {
@@ -72,6 +286,7 @@ mod tests {
                         if let Some(item) = q.pop_front() {
                             break item;
                         } else {
+                            waits += 1;
                             q = not_empty.wait(q);
                         }
                     };
@@ -81,9 +296,264 @@ mod tests {
 
             for i in 0..1000 {
                 queue.lock().push_back(i);
-                not_empty.notify_one();
+                if not_empty.notify_one() {
+                    notified += 1;
+                }
                 std::thread::sleep(std::time::Duration::from_nanos(1));
             }
         });
+
+        // Each wait needs its own counter bump, and only a notify that saw
+        // the waiter bumps the counter.
+        assert!(waits <= notified, "{waits} waits, {notified} notified");
+        assert!(notified <= 1000);
+        assert!(!not_empty.notify_one());
+        not_empty
+    }
+
+    #[test]
+    fn test_debug() {
+        let condvar = Condvar::new();
+        assert_eq!(format!("{condvar:?}"), "Condvar { counter: 0, waiters: 0 }");
+    }
+
+    #[test]
+    fn test_notify_return_values() {
+        let value = Mutex::new(0);
+        let condvar = Condvar::new();
+        assert!(!condvar.notify_one());
+        assert_eq!(condvar.notify_all(), 0);
+
+        std::thread::scope(|s| {
+            for _ in 0..3 {
+                s.spawn(|| {
+                    let mut v = value.lock();
+                    while *v == 0 {
+                        v = condvar.wait(v);
+                    }
+                });
+            }
+            while condvar.waiter.load(Ordering::Relaxed) < 3 {
+                std::thread::yield_now();
+            }
+            *value.lock() = 1;
+            assert_eq!(condvar.notify_all(), 3);
+        });
+        assert_eq!(condvar.notify_all(), 0);
+
+        // A waiter that timed out has deregistered again.
+        let (guard, result) = condvar.wait_timeout(value.lock(), Duration::from_millis(10));
+        assert!(result.timed_out());
+        drop(guard);
+        assert!(!condvar.notify_one());
+    }
+
+    #[test]
+    fn test_wait_timeout_while_success() {
+        let ready = Mutex::new(false);
+        let condvar = Condvar::new();
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                *ready.lock() = true;
+                condvar.notify_one();
+            });
+
+            let start = Instant::now();
+            let (guard, result) =
+                condvar.wait_timeout_while(ready.lock(), Duration::from_secs(2), |ready| !*ready);
+            assert!(!result.timed_out());
+            assert!(*guard);
+            assert!(start.elapsed() < Duration::from_secs(2));
+        });
+    }
+
+    #[test]
+    fn test_wait_timeout_while_timeout() {
+        let value = Mutex::new(0);
+        let condvar = Condvar::new();
+        let done = AtomicBool::new(false);
+        let dur = Duration::from_millis(200);
+
+        std::thread::scope(|s| {
+            // Stale wakeups that never satisfy the predicate must not make the
+            // total wait longer than `dur`.
+            s.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    *value.lock() += 1;
+                    condvar.notify_all();
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            });
+
+            let start = Instant::now();
+            let (guard, result) = condvar.wait_timeout_while(value.lock(), dur, |_| true);
+            let elapsed = start.elapsed();
+            drop(guard);
+            done.store(true, Ordering::Relaxed);
+
+            assert!(result.timed_out());
+            assert!(elapsed >= dur, "returned early: {elapsed:?}");
+            assert!(
+                elapsed < dur + Duration::from_millis(500),
+                "overslept: {elapsed:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_wait_timeout_while_already_false() {
+        let value = Mutex::new(5);
+        let condvar = Condvar::new();
+
+        let start = Instant::now();
+        let (guard, result) =
+            condvar.wait_timeout_while(value.lock(), Duration::from_secs(10), |v| *v != 5);
+        assert!(!result.timed_out());
+        assert_eq!(*guard, 5);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(condvar.waiter.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_wait_timeout_zero() {
+        let value = Mutex::new(());
+        let condvar = Condvar::new();
+        let (guard, result) = condvar.wait_timeout_while(value.lock(), Duration::ZERO, |_| true);
+        assert!(result.timed_out());
+        drop(guard);
+        let (_guard, result) = condvar.wait_timeout(value.lock(), Duration::from_millis(10));
+        assert!(result.timed_out());
+    }
+
+    #[test]
+    fn test_notify_between_unlock_and_sleep() {
+        // Replays `wait` step by step with a notification landing in the
+        // window after the mutex is released but before the futex sleep.
+        let ready = Mutex::new(false);
+        let condvar = Condvar::new();
+
+        let guard = ready.lock();
+        assert!(!*guard);
+        let counter = condvar.register();
+        drop(guard);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                *ready.lock() = true;
+                condvar.notify_one();
+            })
+            .join()
+            .unwrap();
+        });
+
+        // The notifier saw the registered waiter, so it bumped the counter
+        // and the futex wait returns at once instead of sleeping forever.
+        assert_ne!(condvar.counter.load(Ordering::Relaxed), counter);
+        wait(&condvar.counter, counter);
+        condvar.waiter.fetch_sub(1, Ordering::Relaxed);
+        assert!(*ready.lock());
+    }
+
+    #[test]
+    fn test_ping_pong_no_stall() {
+        const ROUNDS: usize = 20_000;
+
+        let turn = Mutex::new(0usize);
+        let condvar = Condvar::new();
+        let progress = std::sync::atomic::AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for parity in 0..2 {
+                let (turn, condvar, progress) = (&turn, &condvar, &progress);
+                s.spawn(move || {
+                    for _ in 0..ROUNDS / 2 {
+                        let mut t = turn.lock();
+                        while *t % 2 != parity {
+                            t = condvar.wait(t);
+                        }
+                        *t += 1;
+                        drop(t);
+                        condvar.notify_all();
+                        progress.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+
+            // A lost wakeup leaves both threads asleep forever; fail loudly
+            // instead of hanging the test run.
+            s.spawn(|| {
+                let mut last = 0;
+                let mut stalled = Instant::now();
+                while progress.load(Ordering::Relaxed) < ROUNDS {
+                    std::thread::sleep(Duration::from_millis(50));
+                    let now = progress.load(Ordering::Relaxed);
+                    if now != last {
+                        last = now;
+                        stalled = Instant::now();
+                    } else if stalled.elapsed() > Duration::from_secs(5) {
+                        eprintln!("condvar ping-pong stalled at {now}/{ROUNDS}");
+                        std::process::abort();
+                    }
+                }
+            });
+        });
+
+        assert_eq!(*turn.lock(), ROUNDS);
+    }
+
+    #[test]
+    fn test_wait_observes_poison() {
+        let ready = Mutex::new(false);
+        let condvar = Condvar::new();
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| {
+                let mut r = ready.lock();
+                while !*r {
+                    r = condvar.wait(r);
+                }
+            });
+            while condvar.waiters() == 0 {
+                std::thread::yield_now();
+            }
+
+            // The notifier wakes the waiter, then panics before releasing.
+            let notifier = s.spawn(|| {
+                let mut r = ready.lock();
+                *r = true;
+                condvar.notify_all();
+                panic!("notifier failed");
+            });
+            assert!(notifier.join().is_err());
+
+            let err = waiter.join().unwrap_err();
+            let msg = err.downcast_ref::<&str>().unwrap();
+            assert!(msg.contains("poisoned"), "{msg}");
+        });
+        assert!(ready.is_poisoned());
+    }
+
+    #[test]
+    fn test_with_fn_wait() {
+        let queue = Mutex::new(VecDeque::new());
+        let not_empty = Condvar::new();
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..100 {
+                    queue.lock().push_back(i);
+                    not_empty.notify_one();
+                }
+            });
+
+            let mut sum = 0;
+            for _ in 0..100 {
+                sum +=
+                    queue.with_fn_wait(&not_empty, |q| !q.is_empty(), |q| q.pop_front().unwrap());
+            }
+            assert_eq!(sum, (0..100).sum::<i32>());
+        });
     }
 }
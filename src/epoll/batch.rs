@@ -0,0 +1,196 @@
+use std::os::fd::{AsRawFd, RawFd};
+
+use super::{
+    CtlOp, EpollError, Interest, Register, Registration, Token, ctl, exclusive_modify_error,
+};
+
+/// Collects registration changes and applies them together with
+/// [`flush`](BatchRegister::flush), e.g. when setting up thousands of
+/// sockets at once.
+///
+/// A failing operation doesn't stop the rest; `flush` reports it under its
+/// token instead. Operations are still applied one `epoll_ctl` at a time,
+/// but callers that go through this don't change if a backend that can
+/// really submit in bulk comes along.
+///
+/// Only fds are queued, so each source must stay open until the flush.
+pub struct BatchRegister {
+    register: Register,
+    ops: Vec<Op>,
+}
+
+struct Op {
+    op: CtlOp,
+    fd: RawFd,
+    interest: Interest,
+    token: Token,
+}
+
+/// What a [`BatchRegister::flush`] did.
+#[derive(Debug, Default)]
+pub struct Flushed {
+    /// The sources added by queued registrations, each staying registered
+    /// until its `Registration` is dropped.
+    pub registered: Vec<(Token, Registration)>,
+    /// The operations that failed, in queue order.
+    pub failed: Vec<(Token, EpollError)>,
+}
+
+impl BatchRegister {
+    pub(super) fn new(register: Register) -> Self {
+        Self {
+            register,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Queues [`Register::register`].
+    pub fn queue_register<S: AsRawFd>(&mut self, source: &S, interest: Interest, token: Token) {
+        self.queue(CtlOp::Add, source, interest, token);
+    }
+
+    /// Queues [`Register::modify`].
+    pub fn queue_modify<S: AsRawFd>(&mut self, source: &S, interest: Interest, token: Token) {
+        self.queue(CtlOp::Modify, source, interest, token);
+    }
+
+    /// Queues [`Register::deregister`]. `token` is only used to report a
+    /// failure.
+    pub fn queue_deregister<S: AsRawFd>(&mut self, source: &S, token: Token) {
+        self.queue(CtlOp::Delete, source, Interest::from_bits(0), token);
+    }
+
+    fn queue<S: AsRawFd>(&mut self, op: CtlOp, source: &S, interest: Interest, token: Token) {
+        self.ops.push(Op {
+            op,
+            fd: source.as_raw_fd(),
+            interest,
+            token,
+        });
+    }
+
+    /// Applies the queued operations in order and empties the queue.
+    pub fn flush(&mut self) -> Flushed {
+        let mut flushed = Flushed::default();
+        for Op {
+            op,
+            fd,
+            interest,
+            token,
+        } in self.ops.drain(..)
+        {
+            let res = match op {
+                CtlOp::Modify if interest.is_exclusive() => Err(exclusive_modify_error(fd)),
+                _ => ctl(&self.register.fd, op, fd, interest.bits(), token),
+            };
+            match res {
+                Ok(()) if op == CtlOp::Add => flushed.registered.push((
+                    token,
+                    Registration::new(self.register.fd.clone(), fd, interest.is_exclusive()),
+                )),
+                Ok(()) => {}
+                Err(e) => flushed.failed.push((token, e)),
+            }
+        }
+        flushed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, os::unix::net::UnixStream, time::Duration};
+
+    use super::*;
+    use crate::epoll::{Events, Poll, RawFdSource};
+
+    #[test]
+    fn test_flush_reports_failures_per_token() {
+        const BAD: Token = Token(42);
+
+        let poll = Poll::new().unwrap();
+        let pairs: Vec<_> = (0..99).map(|_| UnixStream::pair().unwrap()).collect();
+        let mut batch = poll.batch();
+        let mut pairs_iter = pairs.iter();
+        for i in 0..100 {
+            if Token(i) == BAD {
+                batch.queue_register(&RawFdSource(-1), Interest::READABLE, BAD);
+            } else {
+                let (ours, _) = pairs_iter.next().unwrap();
+                batch.queue_register(ours, Interest::READABLE, Token(i));
+            }
+        }
+        assert_eq!(batch.len(), 100);
+
+        let flushed = batch.flush();
+        assert!(batch.is_empty());
+        assert_eq!(flushed.registered.len(), 99);
+        assert_eq!(flushed.failed.len(), 1);
+        let (token, err) = &flushed.failed[0];
+        assert_eq!(*token, BAD);
+        assert!(matches!(
+            err,
+            EpollError::Ctl {
+                op: CtlOp::Add,
+                fd: -1,
+                ..
+            }
+        ));
+
+        // Token 0 and 99 sit on either side of the bad one.
+        (&pairs[0].1).write_all(b"x").unwrap();
+        (&pairs[98].1).write_all(b"x").unwrap();
+        let mut events = Events::with_capacity(8);
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut tokens: Vec<_> = events.iter().map(|e| e.token()).collect();
+        tokens.sort();
+        assert_eq!(tokens, [Token(0), Token(99)]);
+    }
+
+    #[test]
+    fn test_modify_and_deregister() {
+        let poll = Poll::new().unwrap();
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let mut batch = poll.batch();
+        batch.queue_register(&ours, Interest::WRITABLE, Token(1));
+        // Kept alive; dropping its registration would deregister `ours`.
+        let first = batch.flush();
+        assert!(first.failed.is_empty());
+
+        batch.queue_modify(&ours, Interest::READABLE, Token(2));
+        // Not registered: fails, but doesn't stop the modify above.
+        batch.queue_deregister(&theirs, Token(3));
+        batch.queue_modify(&ours, Interest::READABLE.exclusive(), Token(4));
+        let flushed = batch.flush();
+        assert!(flushed.registered.is_empty());
+        let failed: Vec<_> = flushed.failed.iter().map(|(t, e)| (*t, e.kind())).collect();
+        assert_eq!(
+            failed,
+            [
+                (Token(3), std::io::ErrorKind::NotFound),
+                (Token(4), std::io::ErrorKind::InvalidInput)
+            ]
+        );
+
+        let mut events = Events::with_capacity(4);
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty(), "no longer asking for writable");
+        (&theirs).write_all(b"x").unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events[0].token(), Token(2));
+
+        batch.queue_deregister(&ours, Token(2));
+        assert!(batch.flush().failed.is_empty());
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
+    }
+}
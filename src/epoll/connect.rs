@@ -0,0 +1,220 @@
+use std::{
+    io::{Error, ErrorKind},
+    net::{SocketAddr, TcpStream},
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        raw::{c_int, c_void},
+    },
+    time::{Duration, Instant},
+};
+
+use super::{EpollError, Events, Interest, Poll, Result, Token, remaining};
+
+const AF_INET: c_int = 2;
+const AF_INET6: c_int = 10;
+const SOCK_STREAM: c_int = 1;
+const SOCK_NONBLOCK: c_int = 0o4000;
+const SOCK_CLOEXEC: c_int = 0o2000000;
+const SOL_SOCKET: c_int = 1;
+const SO_ERROR: c_int = 4;
+const EINPROGRESS: i32 = 115;
+
+#[repr(C)]
+struct SockAddrIn {
+    sin_family: u16,
+    // Port and address are in network byte order.
+    sin_port: u16,
+    sin_addr: [u8; 4],
+    sin_zero: [u8; 8],
+}
+
+#[repr(C)]
+struct SockAddrIn6 {
+    sin6_family: u16,
+    sin6_port: u16,
+    sin6_flowinfo: u32,
+    sin6_addr: [u8; 16],
+    sin6_scope_id: u32,
+}
+
+mod ffi {
+    use super::*;
+
+    #[link(name = "c")]
+    unsafe extern "C" {
+        pub fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int;
+
+        /// addr points to a sockaddr_in or sockaddr_in6, len is its size
+        pub fn connect(fd: c_int, addr: *const c_void, len: u32) -> c_int;
+
+        pub fn getsockopt(
+            fd: c_int,
+            level: c_int,
+            name: c_int,
+            value: *mut c_void,
+            len: *mut u32,
+        ) -> c_int;
+    }
+}
+
+/// Starts connecting to `addr` without blocking. The returned stream is
+/// non-blocking and usually not connected yet; [`wait_writable`] finishes
+/// the job.
+pub fn connect_nonblocking(addr: SocketAddr) -> Result<TcpStream> {
+    let domain = match addr {
+        SocketAddr::V4(_) => AF_INET,
+        SocketAddr::V6(_) => AF_INET6,
+    };
+    let fd = unsafe { ffi::socket(domain, SOCK_STREAM | SOCK_NONBLOCK | SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(EpollError::syscall("socket", None, Error::last_os_error()));
+    }
+    // SAFETY: `fd` was just created and nothing else owns it. From here on
+    // it is closed on every error path.
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let res = match addr {
+        SocketAddr::V4(addr) => {
+            let raw = SockAddrIn {
+                sin_family: AF_INET as u16,
+                sin_port: addr.port().to_be(),
+                sin_addr: addr.ip().octets(),
+                sin_zero: [0; 8],
+            };
+            let len = size_of::<SockAddrIn>() as u32;
+            unsafe { ffi::connect(fd.as_raw_fd(), (&raw const raw).cast(), len) }
+        }
+        SocketAddr::V6(addr) => {
+            let raw = SockAddrIn6 {
+                sin6_family: AF_INET6 as u16,
+                sin6_port: addr.port().to_be(),
+                sin6_flowinfo: addr.flowinfo(),
+                sin6_addr: addr.ip().octets(),
+                sin6_scope_id: addr.scope_id(),
+            };
+            let len = size_of::<SockAddrIn6>() as u32;
+            unsafe { ffi::connect(fd.as_raw_fd(), (&raw const raw).cast(), len) }
+        }
+    };
+    if res < 0 {
+        let err = Error::last_os_error();
+        if err.raw_os_error() != Some(EINPROGRESS) {
+            return Err(EpollError::syscall("connect", Some(fd.as_raw_fd()), err));
+        }
+    }
+    Ok(TcpStream::from(fd))
+}
+
+/// Waits until `stream` is writable, then reports how its pending connect
+/// ended: `Ok` once connected, or the error the connect failed with (read
+/// from `SO_ERROR`, as a failed `connect`). Gives up with `TimedOut` after
+/// `timeout`.
+///
+/// The wait uses a private epoll instance, so it can't mix up tokens or
+/// swallow events with any `Poll` the caller already has.
+pub fn wait_writable(stream: &TcpStream, timeout: Option<Duration>) -> Result<()> {
+    let poll = Poll::new()?;
+    let _registration = poll
+        .register()
+        .register(stream, Interest::WRITABLE, Token(0))?;
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    let mut events = Events::with_capacity(1);
+    loop {
+        let timeout = deadline.map(|deadline| remaining(deadline, Instant::now()));
+        poll.poll(&mut events, timeout)?;
+        // Errors and hang-ups are reported even though they weren't asked
+        // for; either way `SO_ERROR` tells what happened.
+        if !events.is_empty() {
+            return socket_error(stream);
+        }
+        if timeout == Some(Duration::ZERO) {
+            let err = ErrorKind::TimedOut.into();
+            return Err(EpollError::syscall(
+                "connect",
+                Some(stream.as_raw_fd()),
+                err,
+            ));
+        }
+    }
+}
+
+fn socket_error(stream: &TcpStream) -> Result<()> {
+    let mut err: c_int = 0;
+    let mut len = size_of::<c_int>() as u32;
+    let res = unsafe {
+        ffi::getsockopt(
+            stream.as_raw_fd(),
+            SOL_SOCKET,
+            SO_ERROR,
+            (&raw mut err).cast(),
+            &raw mut len,
+        )
+    };
+    let fd = Some(stream.as_raw_fd());
+    if res < 0 {
+        return Err(EpollError::syscall(
+            "getsockopt",
+            fd,
+            Error::last_os_error(),
+        ));
+    }
+    match err {
+        0 => Ok(()),
+        err => Err(EpollError::syscall(
+            "connect",
+            fd,
+            Error::from_raw_os_error(err),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_nonblocking_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let stream = connect_nonblocking(listener.local_addr().unwrap()).unwrap();
+        wait_writable(&stream, Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), listener.local_addr().unwrap());
+        let (_, peer) = listener.accept().unwrap();
+        assert_eq!(peer, stream.local_addr().unwrap());
+    }
+
+    #[test]
+    fn test_nonblocking_connect_refused() {
+        // Grab a free port and close it again, so nothing listens there.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let stream = connect_nonblocking(addr).unwrap();
+        let err = wait_writable(&stream, Some(Duration::from_secs(1))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+        let fd = stream.as_raw_fd();
+        assert!(matches!(
+            err,
+            EpollError::Syscall { call: "connect", fd: Some(f), .. } if f == fd
+        ));
+        assert!(
+            err.to_string()
+                .starts_with(&format!("connect on fd {fd} failed: "))
+        );
+    }
+
+    #[test]
+    fn test_nonblocking_connect_v6() {
+        let Ok(listener) = TcpListener::bind("[::1]:0") else {
+            // No IPv6 loopback in this environment.
+            return;
+        };
+        let stream = connect_nonblocking(listener.local_addr().unwrap()).unwrap();
+        wait_writable(&stream, Some(Duration::from_secs(1))).unwrap();
+        listener.accept().unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, os::fd::AsRawFd, time::Duration};
+
+use super::{Event, Events, Interest, Poll, Registration, Result, Token};
+
+/// Reacts to the events of one registered source.
+pub trait EventHandler {
+    fn handle(&mut self, event: &Event);
+}
+
+/// Routes events to the handler registered for their token, so an event loop
+/// doesn't need its own token-to-handler bookkeeping.
+///
+/// It owns the [`Poll`] because it hands out the tokens: every registration
+/// has to go through it.
+pub struct EventDispatcher<H> {
+    poll: Poll,
+    handlers: HashMap<Token, Entry<H>>,
+    next_token: usize,
+}
+
+struct Entry<H> {
+    // Declared first so the source is deregistered before a handler that
+    // owns it closes it.
+    _registration: Registration,
+    handler: H,
+}
+
+impl<H: EventHandler> EventDispatcher<H> {
+    pub fn new(poll: Poll) -> Self {
+        Self {
+            poll,
+            handlers: HashMap::new(),
+            next_token: 0,
+        }
+    }
+
+    /// Waits for events, as [`Poll::poll`].
+    pub fn poll(&mut self, events: &mut Events, timeout: Option<Duration>) -> Result<()> {
+        self.poll.poll(events, timeout)
+    }
+
+    /// Registers `source` and remembers `handler` for its events. Returns
+    /// the token picked for it.
+    pub fn register_handler<S: AsRawFd>(
+        &mut self,
+        source: &S,
+        interest: Interest,
+        handler: H,
+    ) -> Result<Token> {
+        let token = Token(self.next_token);
+        let registration = self.poll.register().register(source, interest, token)?;
+        self.next_token += 1;
+        self.handlers.insert(
+            token,
+            Entry {
+                _registration: registration,
+                handler,
+            },
+        );
+        Ok(token)
+    }
+
+    pub fn handler_mut(&mut self, token: Token) -> Option<&mut H> {
+        self.handlers
+            .get_mut(&token)
+            .map(|entry| &mut entry.handler)
+    }
+
+    /// Deregisters the source behind `token` and hands back its handler.
+    pub fn remove_handler(&mut self, token: Token) -> Option<H> {
+        self.handlers.remove(&token).map(|entry| entry.handler)
+    }
+
+    /// Hands each event to its handler and returns how many were handled.
+    /// Events for unknown tokens are skipped.
+    pub fn dispatch(&mut self, events: &[Event]) -> usize {
+        let mut handled = 0;
+        for event in events {
+            if let Some(entry) = self.handlers.get_mut(&event.token()) {
+                entry.handler.handle(event);
+                handled += 1;
+            }
+        }
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::*;
+
+    struct Echo {
+        stream: TcpStream,
+        received: Vec<u8>,
+    }
+
+    impl EventHandler for Echo {
+        fn handle(&mut self, event: &Event) {
+            assert!(event.is_readable());
+            let mut buf = [0; 64];
+            let n = self.stream.read(&mut buf).unwrap();
+            self.received.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    fn pair(listener: &TcpListener) -> (TcpStream, TcpStream) {
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (client, server)
+    }
+
+    #[test]
+    fn test_dispatch_by_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (mut client_a, server_a) = pair(&listener);
+        let (mut client_b, server_b) = pair(&listener);
+
+        let mut events = Events::with_capacity(4);
+        let mut dispatcher = EventDispatcher::new(Poll::new().unwrap());
+        let a = dispatcher
+            .register_handler(
+                &server_a,
+                Interest::READABLE,
+                Echo {
+                    stream: server_a.try_clone().unwrap(),
+                    received: Vec::new(),
+                },
+            )
+            .unwrap();
+        let b = dispatcher
+            .register_handler(
+                &server_b,
+                Interest::READABLE,
+                Echo {
+                    stream: server_b.try_clone().unwrap(),
+                    received: Vec::new(),
+                },
+            )
+            .unwrap();
+        assert_ne!(a, b);
+
+        client_b.write_all(b"to b").unwrap();
+        client_a.write_all(b"to a").unwrap();
+        let mut handled = 0;
+        while handled < 2 {
+            dispatcher
+                .poll(&mut events, Some(Duration::from_secs(1)))
+                .unwrap();
+            assert!(!events.is_empty(), "timed out waiting for events");
+            handled += dispatcher.dispatch(&events);
+        }
+
+        assert_eq!(dispatcher.handler_mut(a).unwrap().received, b"to a");
+        assert_eq!(dispatcher.handler_mut(b).unwrap().received, b"to b");
+
+        // Removing a handler deregisters its source too.
+        let removed = dispatcher.remove_handler(a).unwrap();
+        assert_eq!(removed.received, b"to a");
+        client_a.write_all(b"again").unwrap();
+        dispatcher
+            .poll(&mut events, Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(events.is_empty());
+    }
+}
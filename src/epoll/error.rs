@@ -0,0 +1,168 @@
+use std::{
+    fmt::Display,
+    io::{self, ErrorKind},
+    os::{fd::RawFd, raw::c_int},
+};
+
+use super::{EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD};
+
+/// The `epoll_ctl` operation that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtlOp {
+    Add,
+    Modify,
+    Delete,
+}
+
+impl CtlOp {
+    pub const fn raw(self) -> c_int {
+        match self {
+            CtlOp::Add => EPOLL_CTL_ADD,
+            CtlOp::Modify => EPOLL_CTL_MOD,
+            CtlOp::Delete => EPOLL_CTL_DEL,
+        }
+    }
+}
+
+impl Display for CtlOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CtlOp::Add => "EPOLL_CTL_ADD",
+            CtlOp::Modify => "EPOLL_CTL_MOD",
+            CtlOp::Delete => "EPOLL_CTL_DEL",
+        })
+    }
+}
+
+/// An `io::Error` from the epoll module, plus which call failed and on
+/// which fd.
+///
+/// Converts into `io::Error` (keeping the kind and the context in the
+/// message), so `?` works in functions returning `io::Result`.
+#[derive(Debug)]
+pub enum EpollError {
+    Create(io::Error),
+    Ctl {
+        op: CtlOp,
+        fd: RawFd,
+        source: io::Error,
+    },
+    Wait(io::Error),
+    /// Any other call made for one of the module's sources, e.g.
+    /// `eventfd`, `timerfd_settime`, `signalfd`, `connect`, or a `read` of
+    /// one of those fds. `fd` is the descriptor it was made on, if any.
+    Syscall {
+        call: &'static str,
+        fd: Option<RawFd>,
+        source: io::Error,
+    },
+}
+
+impl EpollError {
+    pub(super) fn syscall(call: &'static str, fd: Option<RawFd>, source: io::Error) -> Self {
+        EpollError::Syscall { call, fd, source }
+    }
+
+    /// The underlying error.
+    pub fn io_error(&self) -> &io::Error {
+        match self {
+            EpollError::Create(e) | EpollError::Wait(e) => e,
+            EpollError::Ctl { source, .. } | EpollError::Syscall { source, .. } => source,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.io_error().kind()
+    }
+
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.io_error().raw_os_error()
+    }
+}
+
+impl Display for EpollError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EpollError::Create(e) => write!(f, "epoll_create1 failed: {e}"),
+            EpollError::Ctl { op, fd, source } => {
+                write!(f, "epoll_ctl({op}) on fd {fd} failed: {source}")
+            }
+            EpollError::Wait(e) => write!(f, "epoll_wait failed: {e}"),
+            EpollError::Syscall {
+                call,
+                fd: Some(fd),
+                source,
+            } => write!(f, "{call} on fd {fd} failed: {source}"),
+            EpollError::Syscall {
+                call,
+                fd: None,
+                source,
+            } => write!(f, "{call} failed: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for EpollError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.io_error())
+    }
+}
+
+impl From<EpollError> for io::Error {
+    fn from(e: EpollError) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpListener,
+        os::fd::{AsRawFd, IntoRawFd},
+    };
+
+    use super::*;
+    use crate::epoll::{Interest, Poll, RawFdSource, Token};
+
+    #[test]
+    fn test_register_closed_fd() {
+        let poll = Poll::new().unwrap();
+        let fd = TcpListener::bind("127.0.0.1:0").unwrap().into_raw_fd();
+        unsafe { crate::epoll::ffi::close(fd) };
+
+        let err = poll
+            .register()
+            .register(&RawFdSource(fd), Interest::READABLE, Token(0))
+            .unwrap_err();
+        assert!(matches!(err, EpollError::Ctl { op: CtlOp::Add, fd: f, .. } if f == fd));
+        assert_eq!(err.raw_os_error(), Some(9));
+        let message = err.to_string();
+        assert!(message.contains("EPOLL_CTL_ADD"), "{message}");
+        assert!(message.contains(&format!("fd {fd}")), "{message}");
+
+        // The context survives the trip into `io::Error`.
+        let kind = err.kind();
+        let io: io::Error = err.into();
+        assert!(io.to_string().contains("EPOLL_CTL_ADD"));
+        assert_eq!(io.kind(), kind);
+    }
+
+    #[test]
+    fn test_modify_unregistered() {
+        let poll = Poll::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let err = poll
+            .register()
+            .modify(&listener, Interest::READABLE, Token(0))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "epoll_ctl(EPOLL_CTL_MOD) on fd {} failed: {}",
+                listener.as_raw_fd(),
+                err.io_error()
+            )
+        );
+    }
+}
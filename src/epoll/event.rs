@@ -0,0 +1,182 @@
+use std::{mem::MaybeUninit, ops::Deref};
+
+use super::{EPOLLERR, EPOLLHUP, EPOLLIN, EPOLLOUT, EPOLLPRI, EPOLLRDHUP, EpollEvent, Token};
+
+/// A readiness event returned by [`Poll::poll`](super::Poll::poll).
+///
+/// It has the same layout as the raw [`EpollEvent`] the kernel writes, so a
+/// buffer of them can be handed to `epoll_wait` directly, but only exposes
+/// safe accessors. [`raw`](Event::raw) is still there to look underneath.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Event(EpollEvent);
+
+impl Event {
+    /// The token the source was registered with.
+    pub fn token(&self) -> Token {
+        self.0.token()
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.events() & EPOLLIN != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.events() & EPOLLOUT != 0
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.events() & EPOLLERR != 0
+    }
+
+    /// The peer closed its writing half (`EPOLLRDHUP`), or the whole
+    /// connection hung up (`EPOLLHUP`).
+    pub fn is_read_closed(&self) -> bool {
+        self.events() & (EPOLLRDHUP | EPOLLHUP) != 0
+    }
+
+    /// Out-of-band data is available (`EPOLLPRI`).
+    pub fn is_priority(&self) -> bool {
+        self.events() & EPOLLPRI != 0
+    }
+
+    pub fn raw(&self) -> &EpollEvent {
+        &self.0
+    }
+
+    fn events(&self) -> u32 {
+        // A by-value read, which is fine even though the struct is packed on
+        // x86_64; only borrowing the field would be unaligned.
+        self.0.events
+    }
+}
+
+impl From<EpollEvent> for Event {
+    fn from(event: EpollEvent) -> Self {
+        Event(event)
+    }
+}
+
+impl std::fmt::Debug for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Event")
+            .field("token", &self.token())
+            .field("readable", &self.is_readable())
+            .field("writable", &self.is_writable())
+            .field("error", &self.is_error())
+            .field("read_closed", &self.is_read_closed())
+            .field("priority", &self.is_priority())
+            .finish()
+    }
+}
+
+/// A fixed-size buffer that [`Poll::poll`](super::Poll::poll) fills with
+/// events. It never grows: when more sources are ready than it can hold, the
+/// rest are reported by the next poll.
+///
+/// It derefs to the events from the last poll.
+pub struct Events {
+    buf: Box<[MaybeUninit<Event>]>,
+    // The first `len` slots were written by the kernel.
+    len: usize,
+}
+
+impl Events {
+    /// A buffer for up to `capacity` events per poll. Polling with a
+    /// capacity of zero fails with `EINVAL`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Box::new_uninit_slice(capacity),
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// The buffer as handed to `epoll_wait`. `Event` is a transparent
+    /// wrapper, so the kernel can write it as an [`EpollEvent`].
+    pub(super) fn as_mut_ptr(&mut self) -> *mut EpollEvent {
+        self.buf.as_mut_ptr().cast()
+    }
+
+    /// # Safety
+    ///
+    /// The first `len` slots must have been initialized, and `len` must not
+    /// exceed the capacity.
+    pub(super) unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity());
+        self.len = len;
+    }
+}
+
+impl Deref for Events {
+    type Target = [Event];
+
+    fn deref(&self) -> &[Event] {
+        // SAFETY: the first `len` slots are initialized, see `set_len`.
+        unsafe { std::slice::from_raw_parts(self.buf.as_ptr().cast(), self.len) }
+    }
+}
+
+impl<'a> IntoIterator for &'a Events {
+    type Item = &'a Event;
+    type IntoIter = std::slice::Iter<'a, Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::fmt::Debug for Events {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epoll::EpollData;
+
+    fn event(events: u32, token: usize) -> Event {
+        Event::from(EpollEvent {
+            events,
+            data: EpollData {
+                u64_val: token as u64,
+            },
+        })
+    }
+
+    #[test]
+    fn test_accessors() {
+        let e = event(EPOLLIN | EPOLLRDHUP, 5);
+        assert_eq!(e.token(), Token(5));
+        assert!(e.is_readable());
+        assert!(!e.is_writable());
+        assert!(!e.is_error());
+        assert!(e.is_read_closed());
+        assert!(!e.is_priority());
+
+        let e = event(EPOLLOUT | EPOLLERR | EPOLLHUP | EPOLLPRI, usize::MAX);
+        assert_eq!(e.token(), Token(usize::MAX));
+        assert!(!e.is_readable());
+        assert!(e.is_writable());
+        assert!(e.is_error());
+        assert!(e.is_read_closed());
+        assert!(e.is_priority());
+        let raw_events = e.raw().events;
+        assert_eq!(raw_events, EPOLLOUT | EPOLLERR | EPOLLHUP | EPOLLPRI);
+    }
+
+    #[test]
+    fn test_layout_matches_raw() {
+        assert_eq!(size_of::<Event>(), size_of::<EpollEvent>());
+        assert_eq!(align_of::<Event>(), align_of::<EpollEvent>());
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use super::{EPOLLET, EPOLLEXCLUSIVE, EPOLLIN, EPOLLONESHOT, EPOLLOUT, EPOLLRDHUP};
+
+/// How readiness is reported for a registration.
+///
+/// Level-triggered is the default and the forgiving choice: a source keeps
+/// showing up in every poll for as long as it is ready, so a handler may
+/// read only part of the data and pick up the rest next time.
+///
+/// Edge-triggered reports a source only when it *becomes* ready. The handler
+/// then has to read (or write) until `WouldBlock`, or it will never hear
+/// about the leftover data again. In exchange, a busy source doesn't show up
+/// in every single poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Trigger {
+    #[default]
+    Level,
+    Edge,
+}
+
+/// The readiness a registration asks for, plus how it is delivered.
+///
+/// Combine readiness with `|` and add delivery modes with the modifiers:
+///
+/// ```
+/// use learn_unsafe::epoll::{Interest, Trigger};
+///
+/// let interest = (Interest::READABLE | Interest::WRITABLE).edge_triggered();
+/// assert!(interest.is_readable() && interest.is_writable());
+/// assert_eq!(interest.trigger(), Trigger::Edge);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interest(u32);
+
+impl Interest {
+    pub const READABLE: Interest = Interest(EPOLLIN);
+    pub const WRITABLE: Interest = Interest(EPOLLOUT);
+    /// The peer shut down its writing half.
+    pub const RDHUP: Interest = Interest(EPOLLRDHUP);
+
+    /// Wraps a raw `EPOLL*` mask, for flags with no named constant here.
+    pub const fn from_bits(bits: u32) -> Self {
+        Interest(bits)
+    }
+
+    /// The raw mask handed to `epoll_ctl`.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Reports a readiness change once instead of for as long as it lasts.
+    pub const fn edge_triggered(self) -> Self {
+        self.with_trigger(Trigger::Edge)
+    }
+
+    pub const fn with_trigger(self, trigger: Trigger) -> Self {
+        match trigger {
+            Trigger::Level => Interest(self.0 & !EPOLLET),
+            Trigger::Edge => Interest(self.0 | EPOLLET),
+        }
+    }
+
+    pub const fn trigger(self) -> Trigger {
+        if self.0 & EPOLLET != 0 {
+            Trigger::Edge
+        } else {
+            Trigger::Level
+        }
+    }
+
+    /// Disables the registration after one event until it is re-armed with
+    /// [`Register::modify`](super::Register::modify).
+    pub const fn oneshot(self) -> Self {
+        Interest(self.0 | EPOLLONESHOT)
+    }
+
+    /// Wakes only one of several epoll instances waiting on the same fd,
+    /// instead of all of them. Meant for a listening socket shared by a few
+    /// polling threads, e.g. through a [`PollPool`](super::PollPool).
+    ///
+    /// The kernel only accepts this when registering: modifying an exclusive
+    /// registration, or asking for exclusive in a modify, fails with
+    /// `InvalidInput`. It can't be combined with [`oneshot`](Self::oneshot)
+    /// either.
+    pub const fn exclusive(self) -> Self {
+        Interest(self.0 | EPOLLEXCLUSIVE)
+    }
+
+    pub const fn is_exclusive(self) -> bool {
+        self.0 & EPOLLEXCLUSIVE != 0
+    }
+
+    pub const fn is_readable(self) -> bool {
+        self.0 & EPOLLIN != 0
+    }
+
+    pub const fn is_writable(self) -> bool {
+        self.0 & EPOLLOUT != 0
+    }
+}
+
+impl BitOr for Interest {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Interest(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Interest {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits_match_kernel_constants() {
+        assert_eq!(Interest::READABLE.bits(), 0x001);
+        assert_eq!(Interest::WRITABLE.bits(), 0x004);
+        assert_eq!(Interest::RDHUP.bits(), 0x2000);
+
+        let mut interest = Interest::READABLE;
+        interest |= Interest::RDHUP;
+        assert_eq!(interest.bits(), EPOLLIN | EPOLLRDHUP);
+        assert_eq!(
+            (Interest::READABLE | Interest::WRITABLE).bits(),
+            EPOLLIN | EPOLLOUT
+        );
+        assert_eq!(
+            Interest::READABLE.edge_triggered().oneshot().bits(),
+            EPOLLIN | EPOLLET | EPOLLONESHOT
+        );
+        assert_eq!(Interest::READABLE.exclusive().bits(), 0x001 | 1 << 28);
+        assert_eq!(Interest::WRITABLE.edge_triggered().bits(), 0x004 | 1 << 31);
+        assert_eq!(Interest::from_bits(EPOLLIN), Interest::READABLE);
+
+        assert_eq!(Interest::READABLE.trigger(), Trigger::Level);
+        let edge = Interest::READABLE.with_trigger(Trigger::Edge);
+        assert_eq!(edge, Interest::READABLE.edge_triggered());
+        assert_eq!(edge.trigger(), Trigger::Edge);
+        assert_eq!(edge.with_trigger(Trigger::Level), Interest::READABLE);
+
+        assert!(Interest::READABLE.is_readable());
+        assert!(!Interest::READABLE.is_writable());
+        assert!(!Interest::RDHUP.oneshot().is_readable());
+        assert!(Interest::READABLE.exclusive().is_exclusive());
+        assert!(!Interest::READABLE.is_exclusive());
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    io::{ErrorKind, Result},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    os::fd::{AsRawFd, RawFd},
+};
+
+/// A `TcpListener` in non-blocking mode, for accepting from an event loop.
+///
+/// Register it for readable events; when one arrives, call
+/// [`accept_all`](NonBlockingListener::accept_all), since one event can
+/// stand for any number of pending connections.
+#[derive(Debug)]
+pub struct NonBlockingListener {
+    inner: TcpListener,
+}
+
+impl NonBlockingListener {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let inner = TcpListener::bind(addr)?;
+        inner.set_nonblocking(true)?;
+        Ok(Self { inner })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Accepts until no connection is pending and hands each one to `f`.
+    ///
+    /// The streams are non-blocking too: accepted sockets don't inherit the
+    /// listener's mode, so this sets it on each.
+    pub fn accept_all(&self, mut f: impl FnMut(TcpStream, SocketAddr)) -> Result<()> {
+        loop {
+            match self.inner.accept() {
+                Ok((stream, addr)) => {
+                    stream.set_nonblocking(true)?;
+                    f(stream, addr);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                // The connection was aborted or the accept interrupted;
+                // others may still be waiting.
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        ErrorKind::ConnectionAborted | ErrorKind::Interrupted
+                    ) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl AsRawFd for NonBlockingListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_all() {
+        let listener = NonBlockingListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Nothing pending: returns at once instead of blocking.
+        let mut accepted = Vec::new();
+        listener.accept_all(|s, _| accepted.push(s)).unwrap();
+        assert!(accepted.is_empty());
+
+        let clients: Vec<_> = (0..3).map(|_| TcpStream::connect(addr).unwrap()).collect();
+        listener
+            .accept_all(|stream, peer| {
+                assert!(clients.iter().any(|c| c.local_addr().unwrap() == peer));
+                accepted.push(stream);
+            })
+            .unwrap();
+        assert_eq!(accepted.len(), 3);
+        let mut buf = [0; 1];
+        let err = std::io::Read::read(&mut accepted[0], &mut buf).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    }
+}
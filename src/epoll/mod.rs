@@ -2,14 +2,50 @@
 #![allow(dead_code)]
 
 use std::{
-    io::Result,
-    net::TcpStream,
+    io::ErrorKind,
     os::{
-        fd::AsRawFd,
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
         raw::{c_int, c_void},
     },
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
+use crate::slab::Slab;
+
+mod batch;
+mod connect;
+pub mod dispatch;
+mod error;
+mod event;
+mod interest;
+mod listener;
+mod pool;
+mod registration;
+mod signalfd;
+mod timerfd;
+mod waker;
+
+pub use batch::{BatchRegister, Flushed};
+pub use connect::{connect_nonblocking, wait_writable};
+pub use dispatch::{EventDispatcher, EventHandler};
+pub use error::{CtlOp, EpollError};
+pub use event::{Event, Events};
+pub use interest::{Interest, Trigger};
+pub use listener::NonBlockingListener;
+pub use pool::PollPool;
+pub use registration::Registration;
+pub use signalfd::{SIGINT, SIGTERM, SIGUSR1, SIGUSR2, SignalFd, SignalInfo};
+pub use timerfd::TimerFd;
+pub use waker::Waker;
+
+/// The result of an epoll call; see [`EpollError`].
+pub type Result<T, E = EpollError> = std::result::Result<T, E>;
+
+/// Identifies a registration; it comes back in every event for that source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Token(pub usize);
+
 #[repr(C)]
 #[cfg_attr(target_arch = "x86_64", repr(packed))]
 #[derive(Debug, Clone, Copy)]
@@ -34,6 +70,17 @@ impl std::fmt::Debug for EpollData {
     }
 }
 
+impl EpollEvent {
+    /// The token this event's source was registered with.
+    pub fn token(&self) -> Token {
+        // Copy the union out first: on x86_64 the struct is packed, so the
+        // field can't be borrowed in place.
+        let data = self.data;
+        // `register` always stores the token in `u64_val`.
+        Token(unsafe { data.u64_val } as usize)
+    }
+}
+
 // epoll 事件类型常量
 pub const EPOLLIN: u32 = 0x001;
 pub const EPOLLPRI: u32 = 0x002;
@@ -69,6 +116,10 @@ mod ffi {
         /// size parameter is ignored but must be greater than 0 (for backward compatibility)
         pub fn epoll_create(size: c_int) -> c_int;
 
+        /// like `epoll_create`, but takes flags instead of the unused size;
+        /// EPOLL_CLOEXEC is the only one
+        pub fn epoll_create1(flags: c_int) -> c_int;
+
         /// controls the epoll instance
         /// epfd: epoll file descriptor
         /// op: operation type (EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD)
@@ -93,22 +144,72 @@ mod ffi {
     }
 }
 
-pub struct Epoll {
-    fd: c_int,
+/// Lets a file descriptor that no std type owns be registered, e.g. one
+/// returned straight from a syscall. It doesn't close the fd on drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawFdSource(pub RawFd);
+
+impl AsRawFd for RawFdSource {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
 }
 
-pub struct Poll {
+/// An epoll instance.
+///
+/// Sources can be registered with explicit tokens through
+/// [`register`](Poll::register), or handed over together with some
+/// per-source state `S` through [`register_with_state`](Poll::register_with_state),
+/// which picks the token itself and keeps the state in a slab under it.
+/// Don't mix the two on one `Poll`: the picked tokens may collide with
+/// explicit ones.
+///
+/// # Threads
+///
+/// [`poll`](Poll::poll) and [`register`](Poll::register) only need
+/// `&self`, and the kernel allows `epoll_ctl` while another thread is in
+/// `epoll_wait` on the same instance. So a `Poll` can be shared (it is
+/// `Sync` when `S` is) and one thread can add sources while another is
+/// blocked waiting; a source added that way is picked up by the wait
+/// already in progress. A clone of the [`Register`] works the same and can
+/// be moved to the other thread instead. To interrupt a wait for any other
+/// reason, use a [`Waker`].
+///
+/// The per-source state needs `&mut self`, so it stays with one thread.
+/// Several threads may wait at once too, but a level-triggered event then
+/// wakes all of them; see [`Interest::exclusive`] and [`PollPool`].
+pub struct Poll<S = ()> {
     register: Register,
+    states: Slab<StateEntry<S>>,
+}
+
+struct StateEntry<S> {
+    // Declared first so the source is deregistered before a state that owns
+    // it closes it.
+    _registration: Registration,
+    state: S,
 }
 
 impl Poll {
+    /// Creates an epoll instance. Its fd is close-on-exec, so it doesn't leak
+    /// into child processes.
     pub fn new() -> Result<Self> {
-        let fd = unsafe { ffi::epoll_create(1) };
+        Self::with_states()
+    }
+}
+
+impl<S> Poll<S> {
+    /// Like [`Poll::new`], for a `Poll` that keeps per-source state.
+    pub fn with_states() -> Result<Self> {
+        let fd = unsafe { ffi::epoll_create1(EPOLL_CLOEXEC) };
         if fd < 0 {
-            return Err(std::io::Error::last_os_error());
+            return Err(EpollError::Create(std::io::Error::last_os_error()));
         }
+        // SAFETY: `fd` was just created and nothing else owns it.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
         Ok(Self {
-            register: Register { fd },
+            register: Register { fd: Arc::new(fd) },
+            states: Slab::new(),
         })
     }
 
@@ -116,49 +217,187 @@ impl Poll {
         &self.register
     }
 
-    pub fn poll(&mut self, events: &mut Vec<EpollEvent>, timeout: Option<c_int>) -> Result<()> {
-        let fd = self.register.fd;
-        let timeout = timeout.unwrap_or(-1);
-        let res =
-            unsafe { ffi::epoll_wait(fd, events.as_mut_ptr(), events.capacity() as i32, timeout) };
-        if res < 0 {
-            return Err(std::io::Error::last_os_error());
-        }
+    /// Starts a batch of registration changes; see [`BatchRegister`].
+    pub fn batch(&self) -> BatchRegister {
+        BatchRegister::new(self.register.clone())
+    }
+
+    /// Registers `source` and stores `state` for it. Returns the token its
+    /// events will carry, which is also the key to the state. Tokens of
+    /// deregistered sources are reused.
+    ///
+    /// When the state owns the source, pass the source as a
+    /// [`RawFdSource`] of its fd, since it can't be borrowed and moved in
+    /// the same call.
+    pub fn register_with_state(
+        &mut self,
+        source: &impl AsRawFd,
+        interest: Interest,
+        state: S,
+    ) -> Result<Token> {
+        let token = Token(self.states.vacant_key());
+        let registration = self.register.register(source, interest, token)?;
+        let key = self.states.insert(StateEntry {
+            _registration: registration,
+            state,
+        });
+        debug_assert_eq!(key, token.0);
+        Ok(token)
+    }
+
+    /// The state stored for `token`, if it is still registered.
+    pub fn state_mut(&mut self, token: Token) -> Option<&mut S> {
+        self.states.get_mut(token.0).map(|entry| &mut entry.state)
+    }
+
+    /// Deregisters the source behind `token` and returns its state.
+    ///
+    /// # Panics
+    ///
+    /// If `token` wasn't handed out by
+    /// [`register_with_state`](Poll::register_with_state) or is already
+    /// deregistered.
+    pub fn deregister(&mut self, token: Token) -> S {
+        self.states.remove(token.0).state
+    }
+
+    /// Waits for events and replaces the contents of `events` with them. At
+    /// most `events.capacity()` are returned per call.
+    ///
+    /// `None` waits until something is ready and `Some(Duration::ZERO)`
+    /// doesn't block at all. Other timeouts are rounded up to whole
+    /// milliseconds. A wait interrupted by a signal is resumed for whatever
+    /// is left of the timeout, so callers never see `EINTR`.
+    pub fn poll(&self, events: &mut Events, timeout: Option<Duration>) -> Result<()> {
+        let fd = self.register.fd.as_raw_fd();
+        // An unrepresentable deadline is as good as waiting forever.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        events.clear();
+        let max = events.capacity().min(c_int::MAX as usize) as c_int;
+        let res = loop {
+            let timeout = deadline.map(|deadline| remaining(deadline, Instant::now()));
+            let res = unsafe { ffi::epoll_wait(fd, events.as_mut_ptr(), max, timeout_ms(timeout)) };
+            if res >= 0 {
+                break res;
+            }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != ErrorKind::Interrupted {
+                return Err(EpollError::Wait(err));
+            }
+        };
+        // SAFETY: the kernel initialized the first `res` entries.
         unsafe { events.set_len(res as usize) };
         Ok(())
     }
 }
 
+impl<S> AsRawFd for Poll<S> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.register.fd.as_raw_fd()
+    }
+}
+
+fn remaining(deadline: Instant, now: Instant) -> Duration {
+    deadline.saturating_duration_since(now)
+}
+
+/// Converts a timeout to `epoll_wait`'s milliseconds, rounding up so a short
+/// non-zero timeout doesn't turn into a busy poll.
+fn timeout_ms(timeout: Option<Duration>) -> c_int {
+    let Some(timeout) = timeout else {
+        return -1;
+    };
+    let ms = timeout.as_nanos().div_ceil(1_000_000);
+    ms.min(c_int::MAX as u128) as c_int
+}
+
+/// Adds and changes registrations of a [`Poll`]. Clones refer to the same
+/// epoll instance, so one can register from another thread while the `Poll`
+/// is blocked waiting.
+#[derive(Clone)]
 pub struct Register {
-    fd: c_int,
+    // Shared with every `Registration`, so each can deregister itself; the
+    // fd is closed once the `Poll` and all of them are gone.
+    fd: Arc<OwnedFd>,
 }
 
 impl Register {
-    pub fn register(&self, source: &TcpStream, interests: u32, token: usize) -> Result<()> {
-        let mut event = EpollEvent {
-            events: interests,
-            data: EpollData {
-                ptr: token as *mut c_void,
-            },
-        };
-        let res =
-            unsafe { ffi::epoll_ctl(self.fd, EPOLL_CTL_ADD, source.as_raw_fd(), &raw mut event) };
-        if res < 0 {
-            return Err(std::io::Error::last_os_error());
+    /// Adds `source` to the epoll set. Any fd-backed type works: sockets,
+    /// pipes, or a bare fd wrapped in [`RawFdSource`].
+    ///
+    /// The source stays registered until the returned [`Registration`] is
+    /// dropped.
+    pub fn register<S: AsRawFd>(
+        &self,
+        source: &S,
+        interest: Interest,
+        token: Token,
+    ) -> Result<Registration> {
+        let fd = source.as_raw_fd();
+        ctl(&self.fd, CtlOp::Add, fd, interest.bits(), token)?;
+        Ok(Registration::new(
+            self.fd.clone(),
+            fd,
+            interest.is_exclusive(),
+        ))
+    }
+
+    /// Changes the interest and token of an already registered source.
+    ///
+    /// This is also how an `EPOLLONESHOT` registration is re-armed after it
+    /// has fired.
+    ///
+    /// Asking for [exclusive](Interest::exclusive) fails with
+    /// `InvalidInput`, since the kernel only accepts it when registering.
+    /// Modifying a source that was registered exclusive is rejected by the
+    /// kernel with `EINVAL`; [`Registration::modify`] catches that too.
+    pub fn modify<S: AsRawFd>(&self, source: &S, interest: Interest, token: Token) -> Result<()> {
+        let fd = source.as_raw_fd();
+        if interest.is_exclusive() {
+            return Err(exclusive_modify_error(fd));
         }
-        Ok(())
+        ctl(&self.fd, CtlOp::Modify, fd, interest.bits(), token)
+    }
+
+    /// Removes `source` from the epoll set. Dropping its [`Registration`]
+    /// does the same.
+    ///
+    /// Closing a fd only drops it from the set once every duplicate of it is
+    /// closed too, so deregister explicitly when a clone may outlive it.
+    pub fn deregister<S: AsRawFd>(&self, source: &S) -> Result<()> {
+        ctl(&self.fd, CtlOp::Delete, source.as_raw_fd(), 0, Token(0))
     }
 }
 
-impl Drop for Register {
-    fn drop(&mut self) {
-        let res = unsafe { ffi::close(self.fd) };
+fn exclusive_modify_error(fd: RawFd) -> EpollError {
+    EpollError::Ctl {
+        op: CtlOp::Modify,
+        fd,
+        source: std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "EPOLLEXCLUSIVE registrations can't be modified; deregister and register again",
+        ),
+    }
+}
 
-        if res < 0 {
-            let err = std::io::Error::last_os_error();
-            println!("Failed to close epoll fd: {}", err);
-        }
+fn ctl(epfd: &OwnedFd, op: CtlOp, fd: RawFd, events: u32, token: Token) -> Result<()> {
+    // `EPOLL_CTL_DEL` ignores the event, but kernels before 2.6.9 reject a
+    // null pointer, so there always is one.
+    let mut event = EpollEvent {
+        events,
+        data: EpollData {
+            u64_val: token.0 as u64,
+        },
+    };
+    let res = unsafe { ffi::epoll_ctl(epfd.as_raw_fd(), op.raw(), fd, &raw mut event) };
+    if res < 0 {
+        return Err(EpollError::Ctl {
+            op,
+            fd,
+            source: std::io::Error::last_os_error(),
+        });
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -166,7 +405,7 @@ mod test {
     use std::{
         collections::{HashMap, HashSet},
         ffi::CStr,
-        io::{BufRead, BufReader, ErrorKind, Read, Write},
+        io::{BufRead, BufReader, Read, Write},
         net::TcpStream,
         os::fd::AsRawFd,
     };
@@ -174,19 +413,19 @@ mod test {
     use super::*;
 
     #[test]
-    #[ignore = "Requires external server running on 127.0.0.1:8080"]
     fn test_ffi_work() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        server.write_all(b"Hello, epoll!\0").unwrap();
+
         unsafe {
             let fd = ffi::epoll_create(1);
             assert!(fd >= 0, "Failed to create epoll instance");
 
             let mut streams = HashMap::new();
-            let mut tcp =
-                TcpStream::connect("127.0.0.1:8080").expect("Failed to connect to server");
             tcp.set_nonblocking(true)
                 .expect("Failed to set non-blocking mode");
-            tcp.write("Hello, epoll!\0".as_bytes())
-                .expect("Failed to write to TCP stream");
             let tcp_fd = tcp.as_raw_fd();
             streams.insert(tcp_fd, tcp);
 
@@ -196,10 +435,11 @@ mod test {
                 events: EPOLLIN | EPOLLET | EPOLLONESHOT,
                 data: EpollData { fd: tcp_fd },
             };
-            // allocate buffer for one event
-            let mut events: Vec<EpollEvent> = Vec::with_capacity(1);
-            // initialize the vector length to hold one event
-            events.set_len(1);
+            // buffer for one event; the kernel overwrites it
+            let mut events = [EpollEvent {
+                events: 0,
+                data: EpollData { u64_val: 0 },
+            }];
             // register the socket with epoll
 
             let ctl_res = ffi::epoll_ctl(fd, EPOLL_CTL_ADD, tcp_fd, &mut event);
@@ -207,7 +447,7 @@ mod test {
 
             // wait for events
             let n = ffi::epoll_wait(fd, events.as_mut_ptr(), 1, 1000);
-            assert!(n >= 0, "epoll_wait failed");
+            assert_eq!(n, 1, "epoll_wait failed");
 
             let n = n as usize;
             for e in &events[..n] {
@@ -215,156 +455,473 @@ mod test {
                 let mut buf = Vec::new();
                 let tcp = streams.get_mut(&fd).expect("Failed to get TCP stream");
                 let mut reader = BufReader::new(tcp);
-                loop {
-                    // Here is a interruption operation
-                    // If the stream is not ready, it will block until data is available
-                    match reader.read_until(0, &mut buf) {
-                        Ok(_) => break,
-                        // Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                        //     // Wait briefly before retrying
-                        //     std::thread::sleep(std::time::Duration::from_millis(50));
-                        //     continue;
-                        // }
-                        Err(e) => panic!("Failed to read from TCP stream: {:?}", e),
-                    }
-                }
-                if buf.is_empty() {
-                    println!("No data read from fd {}", fd);
-                } else {
-                    println!(
-                        "Data read from fd {}: {:?}",
-                        fd,
-                        CStr::from_bytes_with_nul_unchecked(&buf)
-                    );
+                // The whole message arrived in one segment, so this doesn't
+                // run into `WouldBlock`.
+                if let Err(e) = reader.read_until(0, &mut buf) {
+                    panic!("Failed to read from TCP stream: {:?}", e);
                 }
+                assert_eq!(CStr::from_bytes_with_nul(&buf).unwrap(), c"Hello, epoll!");
             }
             ffi::close(fd);
         }
     }
 
-    fn get_req(path: &str) -> String {
-        format!(
-            "GET {path} HTTP/1.1\r\n\
-             Host: localhost\r\n\
-             Connection: close\r\n\
-             \r\n"
-        )
-    }
-
-    fn handle_events_fn(
-        events: &mut Vec<EpollEvent>,
-        streams: &mut Vec<TcpStream>,
-        handled: &mut HashSet<usize>,
-    ) -> Result<usize> {
-        let mut handled_events = 0;
-        for event in events {
-            unsafe {
-                let index = event.data.ptr as usize;
-
-                let mut buf = [0u8; 1024]; // buffer to read data into
-
-                loop {
-                    match streams[index as usize].read(&mut buf) {
-                        Ok(n) if n == 0 => {
-                            // FIX #4
-                            // `insert` returns false if the value already existed in the set.
-                            if !handled.insert(index) {
-                                break;
-                            }
-                            handled_events += 1;
-                            println!("received: {}", String::from_utf8_lossy(&buf));
-                            break;
-                        }
-                        Ok(n) => {
-                            let txt = String::from_utf8_lossy(&buf[..n]);
+    #[test]
+    fn test_register_while_polling() {
+        fn is_sync<T: Sync>(_: &T) {}
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let poll = Poll::new().unwrap();
+        is_sync(&poll);
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| {
+                let mut events = Events::with_capacity(4);
+                // Blocks with nothing registered yet.
+                poll.poll(&mut events, None).unwrap();
+                events.iter().map(Event::token).collect::<Vec<_>>()
+            });
+            // Give the waiter time to block first.
+            std::thread::sleep(Duration::from_millis(50));
+            let registration = poll
+                .register()
+                .register(&server, Interest::READABLE, Token(9))
+                .unwrap();
+            client.write_all(b"x").unwrap();
+            assert_eq!(waiter.join().unwrap(), [Token(9)]);
+            drop(registration);
+        });
+    }
 
-                            println!("RECEIVED: {:?}", event);
-                            println!("{txt}\n------\n");
-                        }
-                        Err(e) if e.kind() == ErrorKind::WouldBlock => {
-                            println!("block");
-                            break;
-                        }
-                        // this was not in the book example, but it's a error condition
-                        // you probably want to handle in some way (either by breaking
-                        // out of the loop or trying a new read call immediately)
-                        Err(e) if e.kind() == ErrorKind::Interrupted => {
-                            println!("interrupted");
-                            break;
-                        }
-                        Err(e) => return Err(e),
-                    }
-                }
-            }
-        }
-        Ok(handled_events)
+    #[test]
+    fn test_modify_and_deregister() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(4);
+        let _registration = poll
+            .register()
+            .register(&server, Interest::READABLE.oneshot(), Token(1))
+            .unwrap();
+
+        client.write_all(b"ping").unwrap();
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(1));
+
+        // The data is still unread, but a one-shot registration stays quiet
+        // until it is re-armed.
+        poll.poll(&mut events, Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(events.is_empty());
+
+        poll.register()
+            .modify(&server, Interest::READABLE.oneshot(), Token(2))
+            .unwrap();
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(2));
+
+        poll.register().deregister(&server).unwrap();
+        client.write_all(b"pong").unwrap();
+        poll.poll(&mut events, Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(events.is_empty());
+
+        // Neither operation works on a source that isn't registered.
+        let err = poll.register().deregister(&server).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(2)); // ENOENT
+        assert!(
+            poll.register()
+                .modify(&server, Interest::READABLE, Token(3))
+                .is_err()
+        );
     }
 
     #[test]
-    #[ignore = "Requires external server running on 127.0.0.1:8080"]
-    fn test_epoll() {
-        let mut epoll = Poll::new().expect("Failed to create epoll instance");
-        let events_len = 10;
-
-        let mut streams = vec![];
-
-        let addr = "127.0.0.1:8080";
-
-        for i in 0..events_len {
-            let delay = 10000 - i * 1000;
-            let url = format!("/{delay}/request_{i}");
-            let request = get_req(&url);
-            let mut stream = TcpStream::connect(addr).expect("Failed to connect to server");
-            stream
-                .set_nonblocking(true)
-                .expect("Failed to set non-blocking mode");
-            stream
-                .write_all(request.as_bytes())
-                .expect("Failed to write to TCP stream");
-            epoll
+    fn test_register_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let _registration = poll
+            .register()
+            .register(&listener, Interest::READABLE, Token(7))
+            .unwrap();
+
+        poll.poll(&mut events, Some(Duration::from_millis(0)))
+            .unwrap();
+        assert!(events.is_empty());
+
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(7));
+        assert!(listener.accept().is_ok());
+    }
+
+    #[test]
+    fn test_register_raw_fd() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let source = RawFdSource(server.as_raw_fd());
+        let registration = poll
+            .register()
+            .register(&source, Interest::READABLE, Token(3))
+            .unwrap();
+
+        client.write_all(b"hi").unwrap();
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(3));
+        registration.deregister().unwrap();
+    }
+
+    #[test]
+    fn test_events_capacity() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(2);
+        assert_eq!(events.capacity(), 2);
+
+        // Nothing is ready: the poll times out empty.
+        poll.poll(&mut events, Some(Duration::from_millis(10)))
+            .unwrap();
+        assert!(events.is_empty());
+
+        let mut pairs = Vec::new();
+        for i in 0..3 {
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (server, _) = listener.accept().unwrap();
+            let registration = poll
                 .register()
-                .register(&stream, EPOLLIN | EPOLLET, i)
-                .expect("Failed to register stream with epoll");
+                .register(&server, Interest::READABLE, Token(i))
+                .unwrap();
+            client.write_all(b"x").unwrap();
+            pairs.push((registration, client, server));
+        }
+        // Let all three become readable before polling.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        let mut seen = HashSet::new();
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        seen.extend(events.iter().map(|e| e.token()));
+
+        // The same buffer is reused; epoll hands out the remaining ready
+        // source before repeating one it has already reported.
+        poll.poll(&mut events, Some(Duration::from_millis(1000)))
+            .unwrap();
+        assert!(!events.is_empty() && events.len() <= 2);
+        seen.extend(events.iter().map(|e| e.token()));
+        assert_eq!(seen, HashSet::from([Token(0), Token(1), Token(2)]));
+
+        for (registration, _, _) in pairs {
+            registration.deregister().unwrap();
+        }
+        poll.poll(&mut events, Some(Duration::from_millis(10)))
+            .unwrap();
+        assert!(events.is_empty());
+    }
 
-            streams.push(stream);
+    #[test]
+    fn test_level_vs_edge_triggered() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        server.set_nonblocking(true).unwrap();
+
+        // The same socket in two epoll sets, one per trigger mode.
+        let level = Poll::new().unwrap();
+        let edge = Poll::new().unwrap();
+        let readable = Interest::READABLE;
+        let _level = level
+            .register()
+            .register(&server, readable.with_trigger(Trigger::Level), Token(0))
+            .unwrap();
+        let _edge = edge
+            .register()
+            .register(&server, readable.with_trigger(Trigger::Edge), Token(0))
+            .unwrap();
+
+        client.write_all(b"data").unwrap();
+        let mut events = Events::with_capacity(1);
+        let short = Some(Duration::from_millis(50));
+
+        edge.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        edge.poll(&mut events, short).unwrap();
+        assert!(events.is_empty(), "edge-triggered fired twice");
+
+        for _ in 0..3 {
+            level.poll(&mut events, short).unwrap();
+            assert_eq!(events.len(), 1, "level-triggered stopped firing");
         }
 
-        let mut handled = HashSet::new();
-        let mut handle_events = 0;
-        while handle_events < events_len {
-            let mut events = Vec::with_capacity(events_len);
-            epoll.poll(&mut events, None).expect("Failed to poll epoll");
+        let mut buf = [0; 16];
+        assert_eq!(server.read(&mut buf).unwrap(), 4);
+        level.poll(&mut events, short).unwrap();
+        assert!(events.is_empty());
+    }
 
-            handle_events += handle_events_fn(&mut events, &mut streams, &mut handled).unwrap();
+    #[test]
+    fn test_cloexec() {
+        let poll = Poll::new().unwrap();
+        let fdinfo =
+            std::fs::read_to_string(format!("/proc/self/fdinfo/{}", poll.as_raw_fd())).unwrap();
+        let flags = fdinfo
+            .lines()
+            .find_map(|line| line.strip_prefix("flags:"))
+            .unwrap();
+        let flags = c_int::from_str_radix(flags.trim(), 8).unwrap();
+        assert_ne!(flags & EPOLL_CLOEXEC, 0, "flags: {flags:o}");
+    }
+
+    #[test]
+    fn test_no_fd_leak() {
+        // Well past the usual 1024 soft limit on open files.
+        for _ in 0..10_000 {
+            Poll::new().unwrap();
         }
+    }
 
+    #[test]
+    fn test_timeout_ms() {
+        assert_eq!(timeout_ms(None), -1);
+        assert_eq!(timeout_ms(Some(Duration::ZERO)), 0);
+        assert_eq!(timeout_ms(Some(Duration::from_nanos(1))), 1);
+        assert_eq!(timeout_ms(Some(Duration::from_micros(999))), 1);
+        assert_eq!(timeout_ms(Some(Duration::from_millis(1))), 1);
+        assert_eq!(timeout_ms(Some(Duration::from_micros(1001))), 2);
+        assert_eq!(timeout_ms(Some(Duration::from_secs(2))), 2000);
+        assert_eq!(timeout_ms(Some(Duration::MAX)), c_int::MAX);
+    }
+
+    #[test]
+    fn test_remaining() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        assert_eq!(remaining(deadline, start), Duration::from_millis(100));
         assert_eq!(
-            events_len, handle_events,
-            "Number of streams should match number of events"
+            remaining(deadline, start + Duration::from_millis(30)),
+            Duration::from_millis(70)
         );
+        // Past the deadline: poll once more without blocking.
+        assert_eq!(remaining(deadline, deadline), Duration::ZERO);
+        assert_eq!(
+            remaining(deadline, deadline + Duration::from_secs(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_poll_timeouts() {
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        // An fd that never becomes ready.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let _registration = poll
+            .register()
+            .register(&listener, Interest::READABLE, Token(0))
+            .unwrap();
+
+        let start = Instant::now();
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        let start = Instant::now();
+        poll.poll(&mut events, Some(Duration::from_millis(100)))
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert!(events.is_empty());
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+
+    #[test]
+    fn test_state_slab() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut poll = Poll::with_states().unwrap();
+        let mut events = Events::with_capacity(4);
+
+        let mut clients = Vec::new();
+        let mut tokens = Vec::new();
+        for name in ["a", "b", "c"] {
+            let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (server, _) = listener.accept().unwrap();
+            let source = RawFdSource(server.as_raw_fd());
+            let token = poll
+                .register_with_state(&source, Interest::READABLE, (name, server))
+                .unwrap();
+            clients.push(client);
+            tokens.push(token);
+        }
+        assert_eq!(tokens, [Token(0), Token(1), Token(2)]);
+
+        clients[1].write_all(b"x").unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(poll.state_mut(events[0].token()).unwrap().0, "b");
+
+        // Deregistering hands the state back and stops its events.
+        let (name, _server) = poll.deregister(Token(1));
+        assert_eq!(name, "b");
+        assert!(poll.state_mut(Token(1)).is_none());
+        poll.poll(&mut events, Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(events.is_empty());
+
+        // The freed token is handed out again.
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let source = RawFdSource(server.as_raw_fd());
+        let token = poll
+            .register_with_state(&source, Interest::READABLE, ("d", server))
+            .unwrap();
+        assert_eq!(token, Token(1));
+        client.write_all(b"y").unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(poll.state_mut(events[0].token()).unwrap().0, "d");
+
+        let next = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let token = poll
+            .register_with_state(&next, Interest::READABLE, ("e", next.try_clone().unwrap()))
+            .unwrap();
+        assert_eq!(token, Token(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid slab key")]
+    fn test_deregister_unknown_token() {
+        let mut poll: Poll<()> = Poll::with_states().unwrap();
+        poll.deregister(Token(0));
+    }
+
+    /// What an echo server keeps per registered source.
+    enum Conn {
+        Listener,
+        Client(TcpStream),
+    }
+
+    /// Echoes one line back on each connection and counts the connections
+    /// that have been closed.
+    fn run_echo_server(
+        poll: &mut Poll<Conn>,
+        listener: &NonBlockingListener,
+        clients: usize,
+    ) -> usize {
+        // A client's read registration is oneshot, so it has to be re-armed
+        // after every event, and only one poll sees it at a time.
+        let interest = Interest::READABLE.oneshot();
+        let mut events = Events::with_capacity(8);
+        let mut rearmed = 0;
+        let mut closed = 0;
+        while closed < clients {
+            poll.poll(&mut events, Some(Duration::from_secs(5)))
+                .unwrap();
+            assert!(!events.is_empty(), "timed out waiting for events");
+            for event in &events {
+                let token = event.token();
+                let mut accepted = Vec::new();
+                let mut rearm = None;
+                let done = match poll.state_mut(token).unwrap() {
+                    Conn::Listener => {
+                        listener
+                            .accept_all(|stream, _| accepted.push(stream))
+                            .unwrap();
+                        false
+                    }
+                    Conn::Client(stream) => {
+                        // One read per event; if more is left, the re-armed
+                        // level-triggered registration fires again at once.
+                        let mut buf = [0; 64];
+                        match stream.read(&mut buf) {
+                            Ok(0) => true,
+                            Ok(n) => {
+                                stream.write_all(&buf[..n]).unwrap();
+                                rearm = Some(RawFdSource(stream.as_raw_fd()));
+                                false
+                            }
+                            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                                rearm = Some(RawFdSource(stream.as_raw_fd()));
+                                false
+                            }
+                            Err(e) => panic!("{e}"),
+                        }
+                    }
+                };
+                if let Some(source) = rearm {
+                    poll.register().modify(&source, interest, token).unwrap();
+                    rearmed += 1;
+                }
+                if done {
+                    poll.deregister(token);
+                    closed += 1;
+                }
+                for stream in accepted {
+                    let source = RawFdSource(stream.as_raw_fd());
+                    poll.register_with_state(&source, interest, Conn::Client(stream))
+                        .unwrap();
+                }
+            }
+        }
+        rearmed
+    }
+
+    #[test]
+    fn test_echo_server() {
+        const CLIENTS: usize = 4;
+        const MESSAGES: usize = 3;
+
+        let listener = NonBlockingListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut poll = Poll::with_states().unwrap();
+        let listener_token = poll
+            .register_with_state(&listener, Interest::READABLE, Conn::Listener)
+            .unwrap();
+
+        std::thread::scope(|s| {
+            for i in 0..CLIENTS {
+                s.spawn(move || {
+                    let stream = TcpStream::connect(addr).unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    for j in 0..MESSAGES {
+                        let line = format!("client {i} message {j}\n");
+                        (&stream).write_all(line.as_bytes()).unwrap();
+                        let mut echoed = String::new();
+                        reader.read_line(&mut echoed).unwrap();
+                        assert_eq!(echoed, line);
+                    }
+                });
+            }
+            let rearmed = run_echo_server(&mut poll, &listener, CLIENTS);
+            // Every client sent data before closing, so each was re-armed
+            // at least once.
+            assert!(rearmed >= CLIENTS);
+        });
+
+        // Only the listener is left.
+        assert!(matches!(poll.deregister(listener_token), Conn::Listener));
+        let mut events = Events::with_capacity(1);
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
     }
 }
-/***
- * My Server code:
- *
- * use axum::{Router, routing::get};
- * use tokio::{net::TcpListener, time::sleep};
- *
- * #[tokio::main]
- * async fn main() {
- *     let app = Router::new().route("/{delay}/request_{i}", get(handle_request));
- *     let listener = TcpListener::bind("127.0.0.1:8080").await.unwrap();
- *     axum::serve(listener, app).await.unwrap();
- * }
- *
- * async fn handle_request(
- *     axum::extract::Path((delay, i)): axum::extract::Path<(usize, usize)>,
- * ) -> String {
- *     println!("Received request with delay: {}, index: {}", delay, i);
- *     sleep(std::time::Duration::from_millis(delay as u64)).await;
- *     println!("sending response for delay: {}, index: {}", delay, i);
- *     format!("Delay: {}, Request: {}", delay, i)
- * }
- *
- *
- */
@@ -0,0 +1,208 @@
+use std::{os::fd::AsRawFd, sync::Arc, thread::JoinHandle};
+
+use super::{
+    EpollError, Event, Events, Interest, Poll, Register, Registration, Result, Token, Waker,
+};
+
+/// Several `Poll`s, each waited on by its own thread, for spreading the
+/// connections of one listening socket over threads.
+///
+/// A source registered through [`register_exclusive`](PollPool::register_exclusive)
+/// is in every poll with [`Interest::exclusive`], so each readiness change
+/// wakes one idle thread rather than all of them. Events go to the handler
+/// together with the index of the thread that got them. Since the kernel
+/// may still wake more than one, the handler should expect `WouldBlock`,
+/// e.g. from an `accept` that another thread beat it to.
+///
+/// Dropping the pool stops and joins the threads.
+pub struct PollPool {
+    threads: Vec<Worker>,
+}
+
+struct Worker {
+    register: Register,
+    shutdown: Waker,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PollPool {
+    /// Reserved for stopping the threads; don't register sources under it.
+    pub const SHUTDOWN: Token = Token(usize::MAX);
+
+    /// Starts `n` threads, each with its own `Poll`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn new<F>(n: usize, handler: F) -> Result<Self>
+    where
+        F: Fn(usize, &Event) + Send + Sync + 'static,
+    {
+        assert!(n > 0, "a PollPool needs at least one thread");
+        let handler = Arc::new(handler);
+        // Built up in place, so an error below still stops the threads
+        // started so far when `pool` is dropped.
+        let mut pool = Self {
+            threads: Vec::with_capacity(n),
+        };
+        for index in 0..n {
+            let poll = Poll::new()?;
+            let shutdown = Waker::new(&poll, Self::SHUTDOWN)?;
+            let register = poll.register().clone();
+            let handler = handler.clone();
+            let thread = std::thread::Builder::new()
+                .name(format!("poll-pool-{index}"))
+                .spawn(move || run(index, &poll, &*handler))
+                .map_err(|err| EpollError::syscall("pthread_create", None, err))?;
+            pool.threads.push(Worker {
+                register,
+                shutdown,
+                thread: Some(thread),
+            });
+        }
+        Ok(pool)
+    }
+
+    pub fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    /// Registers `source` in every poll of the pool with `interest` made
+    /// exclusive. The source stays registered until all the returned
+    /// [`Registration`]s are dropped.
+    ///
+    /// Exclusive registrations can't be modified, so `interest` can't be
+    /// oneshot either.
+    pub fn register_exclusive<S: AsRawFd>(
+        &self,
+        source: &S,
+        interest: Interest,
+        token: Token,
+    ) -> super::Result<Vec<Registration>> {
+        self.threads
+            .iter()
+            .map(|worker| {
+                worker
+                    .register
+                    .register(source, interest.exclusive(), token)
+            })
+            .collect()
+    }
+}
+
+fn run(index: usize, poll: &Poll, handler: &dyn Fn(usize, &Event)) {
+    let mut events = Events::with_capacity(64);
+    loop {
+        if let Err(e) = poll.poll(&mut events, None) {
+            panic!("poll thread {index} failed: {e}");
+        }
+        for event in &events {
+            if event.token() == PollPool::SHUTDOWN {
+                return;
+            }
+            handler(index, event);
+        }
+    }
+}
+
+impl Drop for PollPool {
+    fn drop(&mut self) {
+        for worker in &self.threads {
+            let _ = worker.shutdown.wake();
+        }
+        for worker in &mut self.threads {
+            if let Some(thread) = worker.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        io::ErrorKind,
+        net::{TcpListener, TcpStream},
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_connections_spread_over_threads() {
+        const CONNECTIONS: usize = 20;
+
+        let listener = Arc::new(TcpListener::bind("127.0.0.1:0").unwrap());
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+
+        let pool = {
+            let listener = listener.clone();
+            let accepted = accepted.clone();
+            PollPool::new(2, move |index, event| {
+                assert_eq!(event.token(), Token(0));
+                // One connection per event, so the other thread gets a turn
+                // at the rest.
+                match listener.accept() {
+                    Ok((_, peer)) => accepted.lock().unwrap().push((index, peer)),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(e) => panic!("{e}"),
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            })
+            .unwrap()
+        };
+        assert_eq!(pool.len(), 2);
+        let _registrations = pool
+            .register_exclusive(&*listener, Interest::READABLE, Token(0))
+            .unwrap();
+
+        let clients: Vec<_> = (0..CONNECTIONS)
+            .map(|_| TcpStream::connect(addr).unwrap())
+            .collect();
+        let start = Instant::now();
+        while accepted.lock().unwrap().len() < CONNECTIONS {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        drop(pool);
+
+        let accepted = accepted.lock().unwrap();
+        assert_eq!(accepted.len(), CONNECTIONS);
+        let peers: HashSet<_> = accepted.iter().map(|&(_, peer)| peer).collect();
+        let expected: HashSet<_> = clients.iter().map(|c| c.local_addr().unwrap()).collect();
+        assert_eq!(peers, expected, "a connection was accepted twice");
+        for index in 0..2 {
+            assert!(
+                accepted.iter().any(|&(i, _)| i == index),
+                "thread {index} accepted nothing"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exclusive_modify_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let pool = PollPool::new(1, |_, _| {}).unwrap();
+        let registrations = pool
+            .register_exclusive(&listener, Interest::READABLE, Token(3))
+            .unwrap();
+
+        let err = registrations[0]
+            .modify(Interest::READABLE, Token(3))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = pool.threads[0]
+            .register
+            .modify(&listener, Interest::READABLE.exclusive(), Token(3))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
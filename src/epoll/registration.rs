@@ -0,0 +1,193 @@
+use std::{
+    os::fd::{OwnedFd, RawFd},
+    sync::Arc,
+};
+
+use super::{CtlOp, Interest, Result, Token, ctl, exclusive_modify_error};
+
+/// Keeps a source in an epoll set; dropping it removes the source again.
+///
+/// It owns a handle to the epoll instance rather than borrowing the `Poll`,
+/// so it can live in the same struct as the source it was made for. Declare
+/// it *before* the source there: fields drop in order, and once the source's
+/// fd is closed its number may be reused by an unrelated file, which the
+/// drop would then deregister instead.
+#[derive(Debug)]
+#[must_use = "the source is deregistered as soon as the Registration is dropped"]
+pub struct Registration {
+    epfd: Arc<OwnedFd>,
+    fd: RawFd,
+    // Cleared by an explicit `deregister`, so `Drop` doesn't repeat it.
+    registered: bool,
+    // The kernel refuses to modify an `EPOLLEXCLUSIVE` registration.
+    exclusive: bool,
+}
+
+impl Registration {
+    pub(super) fn new(epfd: Arc<OwnedFd>, fd: RawFd, exclusive: bool) -> Self {
+        Self {
+            epfd,
+            fd,
+            registered: true,
+            exclusive,
+        }
+    }
+
+    /// Changes the interest and token, or re-arms an `EPOLLONESHOT`
+    /// registration.
+    ///
+    /// Fails with `InvalidInput` for an [exclusive](Interest::exclusive)
+    /// registration, or if `interest` asks for exclusive: the kernel only
+    /// accepts that flag when adding. Deregister and register again instead.
+    pub fn modify(&self, interest: Interest, token: Token) -> Result<()> {
+        if self.exclusive || interest.is_exclusive() {
+            return Err(exclusive_modify_error(self.fd));
+        }
+        ctl(&self.epfd, CtlOp::Modify, self.fd, interest.bits(), token)
+    }
+
+    /// Deregisters now and reports any error, which dropping can't.
+    pub fn deregister(mut self) -> Result<()> {
+        self.registered = false;
+        ctl(&self.epfd, CtlOp::Delete, self.fd, 0, Token(0))
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if self.registered {
+            // Fails if the source was already closed, which removed it
+            // anyway.
+            let _ = ctl(&self.epfd, CtlOp::Delete, self.fd, 0, Token(0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        net::{TcpListener, TcpStream},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::epoll::{Events, Poll};
+
+    const SHORT: Option<Duration> = Some(Duration::from_millis(50));
+
+    fn pair(listener: &TcpListener) -> (TcpStream, TcpStream) {
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (client, server)
+    }
+
+    #[test]
+    fn test_drop_stops_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (mut client, server) = pair(&listener);
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+
+        let registration = poll
+            .register()
+            .register(&server, Interest::READABLE, Token(1))
+            .unwrap();
+        client.write_all(b"x").unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+
+        drop(registration);
+        poll.poll(&mut events, SHORT).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_explicit_deregister_then_drop() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (_client, server) = pair(&listener);
+        let poll = Poll::new().unwrap();
+
+        let registration = poll
+            .register()
+            .register(&server, Interest::READABLE, Token(1))
+            .unwrap();
+        // Consumes the registration, whose drop must not deregister again.
+        registration.deregister().unwrap();
+
+        // Deregistered behind the registration's back: the drop's failing
+        // `EPOLL_CTL_DEL` is ignored.
+        let registration = poll
+            .register()
+            .register(&server, Interest::READABLE, Token(2))
+            .unwrap();
+        poll.register().deregister(&server).unwrap();
+        drop(registration);
+    }
+
+    #[test]
+    fn test_modify_through_registration() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (mut client, server) = pair(&listener);
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+
+        let registration = poll
+            .register()
+            .register(&server, Interest::READABLE.oneshot(), Token(1))
+            .unwrap();
+        client.write_all(b"x").unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events[0].token(), Token(1));
+        poll.poll(&mut events, SHORT).unwrap();
+        assert!(events.is_empty());
+
+        registration
+            .modify(Interest::READABLE.oneshot(), Token(2))
+            .unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events[0].token(), Token(2));
+    }
+
+    #[test]
+    fn test_fd_reuse_after_close() {
+        struct Conn {
+            // Dropped before `stream`, see `Registration`.
+            _registration: Registration,
+            stream: TcpStream,
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(4);
+
+        let (mut old_client, server) = pair(&listener);
+        let conn = Conn {
+            _registration: poll
+                .register()
+                .register(&server, Interest::READABLE, Token(1))
+                .unwrap(),
+            stream: server,
+        };
+        old_client.write_all(b"old").unwrap();
+        drop(conn);
+
+        // The lowest free fd number is handed out next, so the new client
+        // most likely reuses the one just closed (other tests running in
+        // parallel may grab it first).
+        let (client, mut server) = pair(&listener);
+        let _registration = poll
+            .register()
+            .register(&client, Interest::READABLE, Token(2))
+            .unwrap();
+        server.write_all(b"new").unwrap();
+
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|event| event.token() == Token(2)));
+    }
+}
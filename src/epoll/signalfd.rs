@@ -0,0 +1,225 @@
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    os::{
+        fd::{AsRawFd, FromRawFd, RawFd},
+        raw::c_int,
+    },
+};
+
+use super::{EpollError, Result};
+
+pub const SIGINT: c_int = 2;
+pub const SIGUSR1: c_int = 10;
+pub const SIGUSR2: c_int = 12;
+pub const SIGTERM: c_int = 15;
+
+const SIG_BLOCK: c_int = 0;
+const SIG_SETMASK: c_int = 2;
+const SFD_CLOEXEC: c_int = 0o2000000;
+const SFD_NONBLOCK: c_int = 0o4000;
+
+/// glibc's `sigset_t`: room for 1024 signals, far more than Linux has.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SigSet([u64; 16]);
+
+/// The fixed 128-byte record a signalfd read returns. Only the leading
+/// fields are spelled out.
+#[repr(C)]
+struct SignalfdSiginfo {
+    ssi_signo: u32,
+    ssi_errno: i32,
+    ssi_code: i32,
+    ssi_pid: u32,
+    ssi_uid: u32,
+    _rest: [u8; 108],
+}
+
+const _: () = assert!(size_of::<SignalfdSiginfo>() == 128);
+
+mod ffi {
+    use super::*;
+
+    #[link(name = "c")]
+    unsafe extern "C" {
+        pub fn sigemptyset(set: *mut SigSet) -> c_int;
+
+        pub fn sigaddset(set: *mut SigSet, signo: c_int) -> c_int;
+
+        pub fn sigismember(set: *const SigSet, signo: c_int) -> c_int;
+
+        /// changes the calling thread's signal mask; the old one is stored in
+        /// old_set unless it is null
+        pub fn pthread_sigmask(how: c_int, set: *const SigSet, old_set: *mut SigSet) -> c_int;
+
+        /// fd -1 creates a new signalfd that reads the signals in mask
+        pub fn signalfd(fd: c_int, mask: *const SigSet, flags: c_int) -> c_int;
+    }
+}
+
+/// A signal read from a [`SignalFd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalInfo {
+    pub signo: c_int,
+    /// The process that sent it.
+    pub pid: u32,
+    pub uid: u32,
+}
+
+/// Receives signals as readable events instead of through a handler, so an
+/// event loop can treat e.g. `SIGTERM` like any other source.
+///
+/// The signals are blocked so they stay pending for the fd to pick up. The
+/// mask is per thread, so create the `SignalFd` before spawning threads that
+/// should inherit it, or block the signals there too; a thread that doesn't
+/// block them still gets the default action. Dropping restores the previous
+/// mask, which delivers any signal still pending in the normal way, so read
+/// them all first.
+///
+/// Because the mask belongs to the thread that created it, a `SignalFd` is
+/// not `Send`.
+pub struct SignalFd {
+    fd: File,
+    old_mask: SigSet,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SignalFd {
+    pub fn new(signals: &[c_int]) -> Result<Self> {
+        let mut mask = MaybeUninit::<SigSet>::uninit();
+        let mask = unsafe {
+            ffi::sigemptyset(mask.as_mut_ptr());
+            mask.assume_init_mut()
+        };
+        for &signo in signals {
+            if unsafe { ffi::sigaddset(mask, signo) } < 0 {
+                let err = std::io::Error::last_os_error();
+                return Err(EpollError::syscall("sigaddset", None, err));
+            }
+        }
+
+        let mut old_mask = MaybeUninit::<SigSet>::uninit();
+        // Returns the error number instead of setting errno.
+        let res = unsafe { ffi::pthread_sigmask(SIG_BLOCK, mask, old_mask.as_mut_ptr()) };
+        if res != 0 {
+            let err = std::io::Error::from_raw_os_error(res);
+            return Err(EpollError::syscall("pthread_sigmask", None, err));
+        }
+        let old_mask = unsafe { old_mask.assume_init() };
+
+        let fd = unsafe { ffi::signalfd(-1, mask, SFD_CLOEXEC | SFD_NONBLOCK) };
+        if fd < 0 {
+            let err = std::io::Error::last_os_error();
+            unsafe { ffi::pthread_sigmask(SIG_SETMASK, &old_mask, std::ptr::null_mut()) };
+            return Err(EpollError::syscall("signalfd", None, err));
+        }
+        Ok(Self {
+            // SAFETY: `fd` was just created and nothing else owns it.
+            fd: unsafe { File::from_raw_fd(fd) },
+            old_mask,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Takes the next pending signal, or `None` if there isn't one.
+    pub fn read_signal(&self) -> Result<Option<SignalInfo>> {
+        let mut buf = [0u8; size_of::<SignalfdSiginfo>()];
+        let error = |err| EpollError::syscall("read", Some(self.fd.as_raw_fd()), err);
+        match (&self.fd).read(&mut buf) {
+            Ok(n) if n != buf.len() => Err(error(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("short signalfd read of {n} bytes"),
+            ))),
+            Ok(_) => {
+                // SAFETY: the buffer is fully initialized, any bytes are a
+                // valid `SignalfdSiginfo`, and the read is unaligned.
+                let info = unsafe { buf.as_ptr().cast::<SignalfdSiginfo>().read_unaligned() };
+                Ok(Some(SignalInfo {
+                    signo: info.ssi_signo as c_int,
+                    pid: info.ssi_pid,
+                    uid: info.ssi_uid,
+                }))
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(error(e)),
+        }
+    }
+}
+
+impl AsRawFd for SignalFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Drop for SignalFd {
+    fn drop(&mut self) {
+        unsafe { ffi::pthread_sigmask(SIG_SETMASK, &self.old_mask, std::ptr::null_mut()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::epoll::{Events, Interest, Poll, Token};
+
+    #[link(name = "c")]
+    unsafe extern "C" {
+        /// sends a signal to the calling thread
+        fn raise(signo: c_int) -> c_int;
+    }
+
+    fn is_blocked(signo: c_int) -> bool {
+        let mut current = MaybeUninit::<SigSet>::uninit();
+        unsafe {
+            ffi::pthread_sigmask(SIG_BLOCK, std::ptr::null(), current.as_mut_ptr());
+            ffi::sigismember(current.as_ptr(), signo) == 1
+        }
+    }
+
+    #[test]
+    fn test_raise_sigusr1() {
+        assert!(!is_blocked(SIGUSR1));
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let signals = SignalFd::new(&[SIGUSR1]).unwrap();
+        assert!(is_blocked(SIGUSR1));
+        let _registration = poll
+            .register()
+            .register(&signals, Interest::READABLE, Token(15))
+            .unwrap();
+        assert_eq!(signals.read_signal().unwrap(), None);
+
+        assert_eq!(unsafe { raise(SIGUSR1) }, 0);
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(15));
+
+        let info = signals.read_signal().unwrap().unwrap();
+        assert_eq!(info.signo, SIGUSR1);
+        assert_eq!(info.pid, std::process::id());
+        assert_eq!(signals.read_signal().unwrap(), None);
+
+        drop(signals);
+        assert!(!is_blocked(SIGUSR1));
+    }
+
+    #[test]
+    fn test_restores_previous_mask() {
+        let outer = SignalFd::new(&[SIGUSR2]).unwrap();
+        let inner = SignalFd::new(&[SIGUSR2, SIGTERM]).unwrap();
+        assert!(is_blocked(SIGUSR2) && is_blocked(SIGTERM));
+        drop(inner);
+        // Back to the outer mask, not to nothing.
+        assert!(is_blocked(SIGUSR2));
+        assert!(!is_blocked(SIGTERM));
+        drop(outer);
+        assert!(!is_blocked(SIGUSR2));
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    os::{
+        fd::{AsRawFd, FromRawFd, RawFd},
+        raw::{c_int, c_long},
+    },
+    time::Duration,
+};
+
+use super::{EpollError, Result};
+
+const CLOCK_MONOTONIC: c_int = 1;
+const TFD_CLOEXEC: c_int = 0o2000000;
+const TFD_NONBLOCK: c_int = 0o4000;
+
+#[repr(C)]
+struct Timespec {
+    tv_sec: c_long,
+    tv_nsec: c_long,
+}
+
+#[repr(C)]
+struct Itimerspec {
+    // Period after the first expiration; zero for a one-shot timer.
+    it_interval: Timespec,
+    // Time until the first expiration; zero disarms the timer.
+    it_value: Timespec,
+}
+
+mod ffi {
+    use super::*;
+
+    #[link(name = "c")]
+    unsafe extern "C" {
+        /// creates a timer that reports expirations through a file descriptor
+        pub fn timerfd_create(clockid: c_int, flags: c_int) -> c_int;
+
+        /// arms (or disarms) the timer; old_value may be null
+        pub fn timerfd_settime(
+            fd: c_int,
+            flags: c_int,
+            new_value: *const Itimerspec,
+            old_value: *mut Itimerspec,
+        ) -> c_int;
+    }
+}
+
+/// A kernel timer that can be registered with [`Poll`](super::Poll) like any
+/// other source. It becomes readable when it expires, which gives each
+/// connection its own timeout instead of one shared poll timeout.
+///
+/// It uses the monotonic clock and is non-blocking.
+pub struct TimerFd {
+    fd: File,
+}
+
+impl TimerFd {
+    /// Expires once, after `after`.
+    pub fn oneshot(after: Duration) -> Result<Self> {
+        Self::new(after, Duration::ZERO)
+    }
+
+    /// Expires every `period`, starting one period from now.
+    ///
+    /// # Panics
+    ///
+    /// If `period` is zero.
+    pub fn interval(period: Duration) -> Result<Self> {
+        assert!(!period.is_zero(), "TimerFd interval must be non-zero");
+        Self::new(period, period)
+    }
+
+    fn new(value: Duration, interval: Duration) -> Result<Self> {
+        let fd = unsafe { ffi::timerfd_create(CLOCK_MONOTONIC, TFD_CLOEXEC | TFD_NONBLOCK) };
+        if fd < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(EpollError::syscall("timerfd_create", None, err));
+        }
+        // SAFETY: `fd` was just created and nothing else owns it.
+        let fd = unsafe { File::from_raw_fd(fd) };
+
+        // An all-zero value would disarm the timer instead of firing at once.
+        let value = value.max(Duration::from_nanos(1));
+        let spec = Itimerspec {
+            it_interval: timespec(interval),
+            it_value: timespec(value),
+        };
+        let res = unsafe {
+            ffi::timerfd_settime(fd.as_raw_fd(), 0, &raw const spec, std::ptr::null_mut())
+        };
+        if res < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(EpollError::syscall(
+                "timerfd_settime",
+                Some(fd.as_raw_fd()),
+                err,
+            ));
+        }
+        Ok(Self { fd })
+    }
+
+    /// Returns how many times the timer expired since the last call, and
+    /// resets that count. 0 means it hasn't expired yet.
+    pub fn read_expirations(&self) -> Result<u64> {
+        let mut buf = [0; 8];
+        match (&self.fd).read(&mut buf) {
+            Ok(_) => Ok(u64::from_ne_bytes(buf)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(0),
+            Err(e) => Err(EpollError::syscall("read", Some(self.fd.as_raw_fd()), e)),
+        }
+    }
+}
+
+impl AsRawFd for TimerFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+fn timespec(duration: Duration) -> Timespec {
+    Timespec {
+        tv_sec: duration.as_secs().min(c_long::MAX as u64) as c_long,
+        tv_nsec: duration.subsec_nanos() as c_long,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::epoll::{Events, Interest, Poll, Token};
+
+    #[test]
+    fn test_oneshot() {
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let start = Instant::now();
+        let timer = TimerFd::oneshot(Duration::from_millis(50)).unwrap();
+        let _registration = poll
+            .register()
+            .register(&timer, Interest::READABLE, Token(9))
+            .unwrap();
+
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].token(), Token(9));
+        assert!(elapsed >= Duration::from_millis(50), "{elapsed:?}");
+        // Generous, to allow for a busy machine.
+        assert!(elapsed < Duration::from_millis(300), "{elapsed:?}");
+
+        assert_eq!(timer.read_expirations().unwrap(), 1);
+        assert_eq!(timer.read_expirations().unwrap(), 0);
+        poll.poll(&mut events, Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_interval() {
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let timer = TimerFd::interval(Duration::from_millis(100)).unwrap();
+        let _registration = poll
+            .register()
+            .register(&timer, Interest::READABLE, Token(0))
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_millis(500);
+        let mut expirations = 0;
+        while Instant::now() < deadline {
+            let left = deadline.saturating_duration_since(Instant::now());
+            poll.poll(&mut events, Some(left)).unwrap();
+            if !events.is_empty() {
+                expirations += timer.read_expirations().unwrap();
+            }
+        }
+        assert!(expirations >= 3, "only {expirations} expirations");
+    }
+
+    #[test]
+    fn test_expirations_accumulate() {
+        let timer = TimerFd::interval(Duration::from_millis(20)).unwrap();
+        assert_eq!(timer.read_expirations().unwrap(), 0);
+        std::thread::sleep(Duration::from_millis(110));
+        // Due at 20, 40, 60, 80 and 100ms.
+        let expirations = timer.read_expirations().unwrap();
+        assert!(expirations >= 5, "{expirations}");
+    }
+
+    #[test]
+    fn test_zero_oneshot_fires() {
+        let timer = TimerFd::oneshot(Duration::ZERO).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(timer.read_expirations().unwrap(), 1);
+    }
+}
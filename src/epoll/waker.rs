@@ -0,0 +1,146 @@
+use std::{
+    fs::File,
+    io::{ErrorKind, Read, Write},
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        raw::{c_int, c_uint},
+    },
+    sync::Arc,
+};
+
+use super::{EpollError, Interest, Poll, Registration, Result, Token};
+
+const EFD_CLOEXEC: c_int = 0o2000000;
+const EFD_NONBLOCK: c_int = 0o4000;
+
+mod ffi {
+    use super::*;
+
+    #[link(name = "c")]
+    unsafe extern "C" {
+        /// creates an eventfd object: a kernel-side u64 counter that is
+        /// readable while non-zero
+        pub fn eventfd(initval: c_uint, flags: c_int) -> c_int;
+    }
+}
+
+/// Wakes a thread blocked in [`Poll::poll`] from any other thread.
+///
+/// It is an `eventfd` registered (level-triggered) under its own token. Any
+/// number of [`wake`](Waker::wake) calls before the poller gets round to it
+/// show up as a single event, which stays ready until
+/// [`drain`](Waker::drain) is called.
+///
+/// Clones share the same eventfd.
+#[derive(Clone)]
+pub struct Waker {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // Deregisters before `fd` is closed.
+    _registration: Registration,
+    fd: File,
+}
+
+impl Waker {
+    pub fn new<S>(poll: &Poll<S>, token: Token) -> Result<Self> {
+        let fd = unsafe { ffi::eventfd(0, EFD_CLOEXEC | EFD_NONBLOCK) };
+        if fd < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(EpollError::syscall("eventfd", None, err));
+        }
+        // SAFETY: `fd` was just created and nothing else owns it.
+        let fd = unsafe { File::from_raw_fd(fd) };
+        let registration = poll.register().register(&fd, Interest::READABLE, token)?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                _registration: registration,
+                fd,
+            }),
+        })
+    }
+
+    pub fn wake(&self) -> Result<()> {
+        match (&self.inner.fd).write(&1u64.to_ne_bytes()) {
+            Ok(_) => Ok(()),
+            // The counter is about to overflow, so a wake-up is pending
+            // anyway.
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(self.error("write", e)),
+        }
+    }
+
+    /// Resets the counter so the waker stops being ready. Call this after
+    /// seeing its event.
+    pub fn drain(&self) -> Result<()> {
+        let mut buf = [0; 8];
+        match (&self.inner.fd).read(&mut buf) {
+            Ok(_) => Ok(()),
+            // Nothing to drain.
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(self.error("read", e)),
+        }
+    }
+
+    fn error(&self, call: &'static str, err: std::io::Error) -> EpollError {
+        EpollError::syscall(call, Some(self.inner.fd.as_raw_fd()), err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::epoll::Events;
+
+    #[test]
+    fn test_wake_blocked_poll() {
+        let poll = Poll::new().unwrap();
+        let waker = Waker::new(&poll, Token(42)).unwrap();
+        let remote = waker.clone();
+
+        let start = Instant::now();
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                remote.wake().unwrap();
+            });
+            let mut events = Events::with_capacity(4);
+            poll.poll(&mut events, None).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].token(), Token(42));
+        });
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_wakes_coalesce() {
+        let poll = Poll::new().unwrap();
+        let waker = Waker::new(&poll, Token(1)).unwrap();
+        let mut events = Events::with_capacity(4);
+
+        for _ in 0..3 {
+            waker.wake().unwrap();
+        }
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert_eq!(events.len(), 1);
+
+        waker.drain().unwrap();
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
+        // Draining twice is harmless.
+        waker.drain().unwrap();
+
+        waker.wake().unwrap();
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_auto_traits() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<Waker>();
+    }
+}
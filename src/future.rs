@@ -2,76 +2,309 @@ use std::{
     pin::Pin,
     sync::{
         Arc,
-        mpsc::{Receiver, SyncSender, sync_channel},
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
     },
-    task::Context,
+    task::{Context, Poll, Wake, Waker},
+    thread::Thread,
+    time::Duration,
 };
 
+use atomic_wait::{wait, wake_all, wake_one};
 use futures::{
     FutureExt,
     task::{self, ArcWake},
 };
 
-use crate::mutex::Mutex;
+use crate::{mutex::Mutex, queue::Queue};
 
+pub mod interval;
+mod join_handle;
+mod local_set;
+#[cfg(target_os = "linux")]
+mod reactor;
 pub mod timer;
 
+pub use join_handle::JoinHandle;
+pub use local_set::LocalSet;
+#[cfg(target_os = "linux")]
+pub use reactor::{AsyncTcpStream, Reactor};
+
+/// Which tasks the executor polls first: it runs every ready `High` task
+/// before any `Normal` one, and every `Normal` before any `Low`. A woken task
+/// is queued at the priority it was spawned with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+struct ReadyQueue {
+    // One queue per `Priority`, highest first.
+    queues: [Queue<Arc<Task>>; 3],
+    // `Queue` is single-consumer. The workers of `run_parallel` take turns
+    // through this futex lock, so a waiting worker sleeps instead of
+    // spinning on the queue's head.
+    pop_lock: Mutex<()>,
+    // Number of live `TaskSender`s; the executor stops once this is zero and
+    // the queue is drained.
+    senders: AtomicUsize,
+    // Bumped after every push and sender drop so the executor can park on it.
+    signal: AtomicU32,
+}
+
+struct TaskSender {
+    shared: Arc<ReadyQueue>,
+}
+
+impl TaskSender {
+    fn send(&self, task: Arc<Task>) {
+        self.shared.queues[task.priority as usize].push(task);
+        self.shared.signal.fetch_add(1, Ordering::SeqCst);
+        wake_one(&self.shared.signal);
+    }
+}
+
+impl Clone for TaskSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for TaskSender {
+    fn drop(&mut self) {
+        self.shared.senders.fetch_sub(1, Ordering::SeqCst);
+        self.shared.signal.fetch_add(1, Ordering::SeqCst);
+        // Every worker of `run_parallel` has to see the last sender go.
+        wake_all(&self.shared.signal);
+    }
+}
+
+/// Running totals for one executor, shared by it and its spawner. All
+/// counters are relaxed, so reads are only loosely in sync with each other.
+#[derive(Debug, Default)]
+pub struct ExecutorMetrics {
+    tasks_spawned: AtomicU64,
+    tasks_completed: AtomicU64,
+    total_polls: AtomicU64,
+}
+
+impl ExecutorMetrics {
+    pub fn tasks_spawned(&self) -> u64 {
+        self.tasks_spawned.load(Ordering::Relaxed)
+    }
+
+    pub fn tasks_completed(&self) -> u64 {
+        self.tasks_completed.load(Ordering::Relaxed)
+    }
+
+    /// Polls across all tasks; more than `tasks_completed` whenever tasks
+    /// had to wait.
+    pub fn total_polls(&self) -> u64 {
+        self.total_polls.load(Ordering::Relaxed)
+    }
+}
+
 pub struct Executor {
-    ready_queue: Receiver<Arc<Task>>,
+    ready_queue: Arc<ReadyQueue>,
+    metrics: Arc<ExecutorMetrics>,
 }
 
 impl Executor {
+    pub fn metrics(&self) -> &ExecutorMetrics {
+        &self.metrics
+    }
+
     pub fn run(&self) {
-        while let Ok(task) = self.ready_queue.recv() {
-            let mut future_slot = task.future.lock();
-            if let Some(mut future) = future_slot.take() {
-                let waker = task::waker_ref(&task);
-                let context = &mut Context::from_waker(&waker);
-
-                if future.as_mut().poll(context).is_pending() {
-                    *future_slot = Some(future);
+        loop {
+            let signal = self.ready_queue.signal.load(Ordering::SeqCst);
+            match self.ready_queue.pop() {
+                Some(task) => {
+                    // Holding the slot's lock while polling keeps another
+                    // worker that popped the same task from polling it too.
+                    let mut future_slot = task.future.lock();
+                    // Cleared before the poll so that a wake during it queues
+                    // the task again.
+                    task.scheduled.swap(false, Ordering::AcqRel);
+                    if let Some(mut future) = future_slot.take() {
+                        let waker = task::waker_ref(&task);
+                        let context = &mut Context::from_waker(&waker);
+
+                        self.metrics.total_polls.fetch_add(1, Ordering::Relaxed);
+                        if future.as_mut().poll(context).is_pending() {
+                            *future_slot = Some(future);
+                        } else {
+                            self.metrics.tasks_completed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
                 }
+                None if self.ready_queue.senders.load(Ordering::SeqCst) == 0 => break,
+                None => wait(&self.ready_queue.signal, signal),
             }
         }
     }
+
+    /// Like [`run`](Executor::run), but with `n_threads` workers pulling from
+    /// the same queue: the current thread plus `n_threads - 1` spawned ones.
+    /// Returns once every worker has run out of tasks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_threads` is zero.
+    pub fn run_parallel(self, n_threads: usize) {
+        assert!(n_threads > 0, "run_parallel needs at least one thread");
+        std::thread::scope(|s| {
+            for _ in 1..n_threads {
+                s.spawn(|| self.run());
+            }
+            self.run();
+        });
+    }
+}
+
+impl ReadyQueue {
+    fn pop(&self) -> Option<Arc<Task>> {
+        let _consumer = self.pop_lock.lock();
+        self.queues.iter().find_map(Queue::pop)
+    }
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        // Queued tasks own a sender back into this queue; drain them to break
+        // the cycle if the executor is dropped without being run.
+        while self.ready_queue.pop().is_some() {}
+    }
 }
 
 pub struct Spawner {
-    task_sender: SyncSender<Arc<Task>>,
+    task_sender: TaskSender,
+    metrics: Arc<ExecutorMetrics>,
 }
 
 impl Spawner {
     pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        self.spawn_with_priority(future, Priority::Normal);
+    }
+
+    pub fn spawn_with_priority(
+        &self,
+        future: impl Future<Output = ()> + Send + 'static,
+        priority: Priority,
+    ) {
         let future = future.boxed();
         let task = Arc::new(Task {
             future: Mutex::new(Some(future)),
+            priority,
+            scheduled: AtomicBool::new(true),
             task_sender: self.task_sender.clone(),
         });
-        self.task_sender.send(task).expect("task queue full");
+        self.metrics.tasks_spawned.fetch_add(1, Ordering::Relaxed);
+        self.task_sender.send(task);
+    }
+
+    /// Like [`spawn`](Spawner::spawn), for a future with an output, which
+    /// the returned handle yields once the task is done.
+    pub fn spawn_with_handle<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> JoinHandle<T> {
+        let (handle, completer) = JoinHandle::new();
+        self.spawn(async move { completer.complete(future.await) });
+        handle
+    }
+
+    /// Spawns `future` once `delay` has passed. The task is queued right
+    /// away but sits on a [`Timer`](timer::Timer) first, so it keeps the
+    /// executor running until it is done.
+    pub fn spawn_after(&self, delay: Duration, future: impl Future<Output = ()> + Send + 'static) {
+        self.spawn(async move {
+            // Nothing else holds a cancel handle, so this can't fail.
+            let _ = timer::Timer::new(delay).await;
+            future.await;
+        });
     }
 }
 
 pub struct Task {
     future: Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send + 'static>>>>,
+    priority: Priority,
+    // Set while the task sits in the ready queue, so repeated wakes queue it
+    // only once.
+    scheduled: AtomicBool,
 
-    task_sender: SyncSender<Arc<Task>>,
+    task_sender: TaskSender,
 }
 
 impl ArcWake for Task {
     fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
-        let cloned = arc_self.clone();
-        arc_self.task_sender.send(cloned).expect("task queue full");
+        if !arc_self.scheduled.swap(true, Ordering::AcqRel) {
+            let cloned = arc_self.clone();
+            arc_self.task_sender.send(cloned);
+        }
+    }
+}
+
+// Wakes `block_on` by unparking the thread it runs on.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs `future` to completion on the current thread, parking it while the
+/// future is pending. For a single future this saves setting up an
+/// [`Executor`] and [`Spawner`].
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        // A wake that lands before we park leaves the token set, so this
+        // returns at once; spurious returns just cost an extra poll.
+        std::thread::park();
     }
 }
 
 pub fn new_executor_and_spawner() -> (Executor, Spawner) {
-    let (task_sender, ready_queue) = sync_channel(10_000);
-    (Executor { ready_queue }, Spawner { task_sender })
+    let ready_queue = Arc::new(ReadyQueue {
+        queues: [Queue::new(), Queue::new(), Queue::new()],
+        pop_lock: Mutex::new(()),
+        senders: AtomicUsize::new(1),
+        signal: AtomicU32::new(0),
+    });
+    let task_sender = TaskSender {
+        shared: ready_queue.clone(),
+    };
+    let metrics = Arc::new(ExecutorMetrics::default());
+    (
+        Executor {
+            ready_queue,
+            metrics: metrics.clone(),
+        },
+        Spawner {
+            task_sender,
+            metrics,
+        },
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::time::Instant;
 
     use crate::future::timer::Timer;
 
@@ -96,7 +329,7 @@ mod tests {
 
         spawner.spawn(async {
             println!("howdy!");
-            Timer::new(Duration::from_secs(2)).await;
+            Timer::new(Duration::from_secs(2)).await.unwrap();
             println!("done!");
         });
 
@@ -111,19 +344,19 @@ mod tests {
 
         spawner.spawn(async {
             println!("Task 1 started");
-            Timer::new(Duration::from_secs(3)).await;
+            Timer::new(Duration::from_secs(3)).await.unwrap();
             println!("Task 1 finished (3s)");
         });
 
         spawner.spawn(async {
             println!("Task 2 started");
-            Timer::new(Duration::from_secs(1)).await;
+            Timer::new(Duration::from_secs(1)).await.unwrap();
             println!("Task 2 finished (1s)");
         });
 
         spawner.spawn(async {
             println!("Task 3 started");
-            Timer::new(Duration::from_secs(2)).await;
+            Timer::new(Duration::from_secs(2)).await.unwrap();
             println!("Task 3 finished (2s)");
         });
 
@@ -131,4 +364,186 @@ mod tests {
 
         executor.run();
     }
+
+    #[test]
+    fn test_spawn_after() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let start = Instant::now();
+        let order = Arc::new(std::sync::Mutex::new(std::vec::Vec::new()));
+
+        let log = order.clone();
+        spawner.spawn_after(Duration::from_millis(50), async move {
+            log.lock().unwrap().push(("delayed", start.elapsed()));
+        });
+        let log = order.clone();
+        spawner.spawn(async move {
+            log.lock().unwrap().push(("immediate", start.elapsed()));
+        });
+        drop(spawner);
+        executor.run();
+
+        let order = order.lock().unwrap();
+        assert_eq!(order[0].0, "immediate");
+        assert_eq!(order[1].0, "delayed");
+        assert!(order[1].1 >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_priority() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let order = Arc::new(std::sync::Mutex::new(std::vec::Vec::new()));
+
+        for (name, priority) in [
+            ("low", Priority::Low),
+            ("normal", Priority::Normal),
+            ("high", Priority::High),
+        ] {
+            let log = order.clone();
+            spawner.spawn_with_priority(
+                async move {
+                    log.lock().unwrap().push(name);
+                },
+                priority,
+            );
+        }
+        let log = order.clone();
+        spawner.spawn(async move {
+            log.lock().unwrap().push("default");
+        });
+        drop(spawner);
+        executor.run();
+
+        assert_eq!(*order.lock().unwrap(), ["high", "normal", "default", "low"]);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let (executor, spawner) = new_executor_and_spawner();
+        for _ in 0..3 {
+            spawner.spawn(async {});
+        }
+        spawner.spawn(async {
+            Timer::new(Duration::from_millis(10)).await.unwrap();
+        });
+        assert_eq!(executor.metrics().tasks_spawned(), 4);
+        assert_eq!(executor.metrics().total_polls(), 0);
+        drop(spawner);
+        executor.run();
+
+        let metrics = executor.metrics();
+        assert_eq!(metrics.tasks_completed(), 4);
+        // The timer task is polled once before and once after it fires.
+        assert_eq!(metrics.total_polls(), 5);
+    }
+
+    // Pending for `yields` polls, waking itself twice each time, and flags
+    // any poll that overlaps another of the same task.
+    struct Yield {
+        yields: usize,
+        polling: Arc<AtomicBool>,
+    }
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            assert!(
+                !self.polling.swap(true, Ordering::SeqCst),
+                "task polled concurrently"
+            );
+            std::thread::yield_now();
+            let result = if self.yields == 0 {
+                Poll::Ready(())
+            } else {
+                self.yields -= 1;
+                cx.waker().wake_by_ref();
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            };
+            self.polling.store(false, Ordering::SeqCst);
+            result
+        }
+    }
+
+    #[test]
+    fn test_duplicate_wakes_queue_once() {
+        let (executor, spawner) = new_executor_and_spawner();
+        spawner.spawn(Yield {
+            yields: 3,
+            polling: Arc::default(),
+        });
+        drop(spawner);
+        executor.run();
+        assert_eq!(executor.metrics().total_polls(), 4);
+    }
+
+    #[test]
+    fn test_run_parallel() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let completed = Arc::new(std::vec::Vec::from_iter(
+            (0..1005).map(|_| AtomicUsize::new(0)),
+        ));
+
+        for i in 0..1000 {
+            let completed = completed.clone();
+            spawner.spawn(async move {
+                Yield {
+                    yields: i % 4,
+                    polling: Arc::default(),
+                }
+                .await;
+                completed[i].fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        for i in 1000..1005 {
+            let completed = completed.clone();
+            spawner.spawn(async move {
+                Timer::new(Duration::from_millis(20)).await.unwrap();
+                completed[i].fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        drop(spawner);
+
+        let metrics = executor.metrics.clone();
+        executor.run_parallel(4);
+        assert!(completed.iter().all(|n| n.load(Ordering::Relaxed) == 1));
+        assert_eq!(metrics.tasks_completed(), 1005);
+    }
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(block_on(async { 6 * 7 }), 42);
+
+        let output = block_on(async {
+            Timer::new(Duration::from_millis(20)).await.unwrap();
+            Timer::new(Duration::from_millis(20)).await.unwrap();
+            "done"
+        });
+        assert_eq!(output, "done");
+    }
+
+    #[test]
+    fn test_block_on_woken_from_other_thread() {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            sender.send(5).unwrap();
+        });
+        assert_eq!(block_on(receiver), Ok(5));
+    }
+
+    #[test]
+    fn test_drop_without_run() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let flag = Arc::new(());
+
+        let held = flag.clone();
+        spawner.spawn(async move {
+            drop(held);
+        });
+
+        drop(spawner);
+        drop(executor);
+        assert_eq!(Arc::strong_count(&flag), 1);
+    }
 }
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use super::timer::Timer;
+
+/// Fires every `period`, as measured from when it was created.
+///
+/// Ticks are scheduled against fixed deadlines, so a late tick doesn't push
+/// the later ones back. If the caller falls behind, up to `max_burst` of the
+/// missed ticks fire back to back; anything beyond that is dropped and the
+/// schedule restarts from the current time, so a long stall can't turn into
+/// a flood of ticks.
+pub struct Interval {
+    period: Duration,
+    next_deadline: Instant,
+    // Late ticks fired in a row without waiting.
+    missed_ticks: u32,
+    max_burst: u32,
+}
+
+impl Interval {
+    /// The first tick fires one `period` from now.
+    ///
+    /// # Panics
+    ///
+    /// If `period` is zero.
+    pub fn new(period: Duration, max_burst: u32) -> Self {
+        assert!(!period.is_zero(), "Interval period must be non-zero");
+        Self {
+            period,
+            next_deadline: Instant::now() + period,
+            missed_ticks: 0,
+            max_burst,
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Waits for the next tick and returns the deadline it was scheduled for.
+    pub async fn tick(&mut self) -> Instant {
+        let now = Instant::now();
+        if now >= self.next_deadline {
+            if self.missed_ticks < self.max_burst {
+                self.missed_ticks += 1;
+                let deadline = self.next_deadline;
+                self.next_deadline += self.period;
+                return deadline;
+            }
+            // Out of burst budget: forget the backlog.
+            self.next_deadline = now + self.period;
+        }
+        self.missed_ticks = 0;
+
+        let deadline = self.next_deadline;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        // No one else can cancel this timer.
+        let _ = Timer::new(remaining).await;
+        self.next_deadline += self.period;
+        deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::future::block_on;
+
+    const PERIOD: Duration = Duration::from_millis(30);
+
+    #[test]
+    fn test_ticks_are_spaced() {
+        let mut interval = Interval::new(PERIOD, 2);
+        let mut expected = interval.next_deadline;
+        block_on(async {
+            for _ in 0..3 {
+                assert_eq!(interval.tick().await, expected);
+                assert!(Instant::now() >= expected);
+                expected += PERIOD;
+            }
+        });
+    }
+
+    #[test]
+    fn test_slow_handler_bursts_at_most_max_burst() {
+        let mut interval = Interval::new(PERIOD, 2);
+        block_on(async {
+            interval.tick().await;
+            // A slow handler misses about ten ticks.
+            std::thread::sleep(PERIOD * 10);
+
+            let mut immediate = 0;
+            loop {
+                let before = Instant::now();
+                interval.tick().await;
+                if before.elapsed() >= PERIOD / 2 {
+                    break;
+                }
+                immediate += 1;
+            }
+            assert_eq!(immediate, 2);
+
+            // Back on schedule: the next tick waits a full period again.
+            let before = Instant::now();
+            interval.tick().await;
+            assert!(before.elapsed() >= PERIOD / 2);
+        });
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{cond_var::Condvar, mutex::Mutex};
+
+/// The output of a task spawned with
+/// [`Spawner::spawn_with_handle`](super::Spawner::spawn_with_handle).
+///
+/// Await it from another task, or [`join`](JoinHandle::join) it from a
+/// thread outside the executor. Dropping the handle detaches the task: it
+/// still runs to completion, and its output is dropped.
+///
+/// If the task panics, or is dropped before it finishes (say, with its
+/// executor), there is no output: `join` and awaiting the handle panic.
+pub struct JoinHandle<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Hands the output to the [`JoinHandle`]. Owned by the task's future, so if
+/// that is dropped before `complete` runs, the handle learns it was
+/// abandoned.
+pub(super) struct Completer<T> {
+    shared: Arc<Shared<T>>,
+    completed: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    // For `join`; a task waiting on the handle is woken through `waker`.
+    finished: Condvar,
+}
+
+struct State<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+    // Set once the output has been handed out, to catch a second poll.
+    taken: bool,
+    // Set if the task went away without an output.
+    abandoned: bool,
+}
+
+impl<T> JoinHandle<T> {
+    pub(super) fn new() -> (Self, Completer<T>) {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                output: None,
+                waker: None,
+                taken: false,
+                abandoned: false,
+            }),
+            finished: Condvar::new(),
+        });
+        (
+            Self {
+                shared: shared.clone(),
+            },
+            Completer {
+                shared,
+                completed: false,
+            },
+        )
+    }
+
+    /// Blocks the thread until the task has finished and returns its
+    /// output.
+    ///
+    /// Don't call this on the executor's own thread: the task can't make
+    /// progress while it is blocked, so this never returns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task panicked or was dropped before it finished.
+    pub fn join(self) -> T {
+        let mut state = self.shared.state.lock();
+        loop {
+            if let Some(output) = state.output.take() {
+                return output;
+            }
+            state.check_pending();
+            state = self.shared.finished.wait(state);
+        }
+    }
+
+    /// Whether the task has finished, i.e. whether `join` would return (or
+    /// panic) at once.
+    pub fn is_finished(&self) -> bool {
+        let state = self.shared.state.lock();
+        state.output.is_some() || state.abandoned
+    }
+}
+
+impl<T> State<T> {
+    // Panics if no output is coming.
+    fn check_pending(&self) {
+        assert!(
+            !self.abandoned,
+            "task panicked or was dropped before it finished"
+        );
+        assert!(!self.taken, "JoinHandle polled after completion");
+    }
+}
+
+impl<T> Completer<T> {
+    pub(super) fn complete(mut self, output: T) {
+        self.completed = true;
+        self.shared.finish(Some(output));
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.shared.finish(None);
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    fn finish(&self, output: Option<T>) {
+        let mut state = self.state.lock();
+        match output {
+            Some(output) => state.output = Some(output),
+            None => state.abandoned = true,
+        }
+        let waker = state.waker.take();
+        drop(state);
+        self.finished.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.shared.state.lock();
+        if let Some(output) = state.output.take() {
+            state.taken = true;
+            return Poll::Ready(output);
+        }
+        state.check_pending();
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic::AssertUnwindSafe, time::Duration};
+
+    use crate::future::{new_executor_and_spawner, timer::Timer};
+
+    #[test]
+    fn test_await_from_another_task() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let answer = spawner.spawn_with_handle(async {
+            Timer::new(Duration::from_millis(10)).await.unwrap();
+            6 * 7
+        });
+        let doubled = spawner.spawn_with_handle(async move { answer.await * 2 });
+        drop(spawner);
+        executor.run();
+        assert!(doubled.is_finished());
+        assert_eq!(doubled.join(), 84);
+    }
+
+    #[test]
+    fn test_join_from_outside() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let handle = spawner.spawn_with_handle(async {
+            Timer::new(Duration::from_millis(20)).await.unwrap();
+            String::from("done")
+        });
+        drop(spawner);
+        let runner = std::thread::spawn(move || executor.run());
+        assert_eq!(handle.join(), "done");
+        runner.join().unwrap();
+    }
+
+    #[test]
+    fn test_dropped_handle_detaches() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        drop(spawner.spawn_with_handle(async move {
+            sender.send(1).unwrap();
+        }));
+        drop(spawner);
+        executor.run();
+        assert_eq!(receiver.try_recv(), Ok(1));
+    }
+
+    #[test]
+    fn test_panicking_task() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let handle = spawner.spawn_with_handle(async {
+            Timer::new(Duration::from_millis(10)).await.unwrap();
+            panic!("task failed");
+        });
+        drop(spawner);
+        let runner = std::thread::spawn(move || executor.run());
+
+        let joined = std::panic::catch_unwind(AssertUnwindSafe(|| handle.join()));
+        let message = joined.unwrap_err();
+        assert_eq!(
+            message.downcast_ref::<&str>(),
+            Some(&"task panicked or was dropped before it finished")
+        );
+        // The panic went through the executor as well.
+        assert!(runner.join().is_err());
+    }
+
+    #[test]
+    fn test_dropped_task() {
+        let (executor, spawner) = new_executor_and_spawner();
+        let handle = spawner.spawn_with_handle(async { 1 });
+        assert!(!handle.is_finished());
+        drop(executor);
+        assert!(handle.is_finished());
+
+        // A task awaiting the handle sees it too.
+        let (executor, spawner) = new_executor_and_spawner();
+        let waiting = spawner.spawn_with_handle(handle);
+        drop(spawner);
+        let run = std::panic::catch_unwind(AssertUnwindSafe(|| executor.run()));
+        assert!(run.is_err());
+        assert!(waiting.is_finished());
+    }
+}
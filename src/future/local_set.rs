@@ -0,0 +1,125 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Waker},
+};
+
+/// A batch of futures driven by hand on the current thread.
+///
+/// There is no reactor and no sleeping: [`run_until_stall`](Self::run_until_stall)
+/// polls every task once and keeps the ones that are still pending for the
+/// next call. Wake-ups are ignored, so the caller decides when to run again.
+/// Tasks never leave the thread, so they don't have to be `Send`.
+pub struct LocalSet {
+    tasks: VecDeque<Pin<Box<dyn Future<Output = ()>>>>,
+}
+
+impl LocalSet {
+    pub fn new() -> Self {
+        Self {
+            tasks: VecDeque::new(),
+        }
+    }
+
+    pub fn spawn_local(&mut self, future: impl Future<Output = ()> + 'static) {
+        self.tasks.push_back(Box::pin(future));
+    }
+
+    /// Polls each task once, dropping the ones that finish.
+    pub fn run_until_stall(&mut self) {
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..self.tasks.len() {
+            let mut task = self.tasks.pop_front().unwrap();
+            if task.as_mut().poll(&mut cx).is_pending() {
+                self.tasks.push_back(task);
+            }
+        }
+    }
+
+    /// Tasks that haven't finished yet.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+impl Default for LocalSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, task::Poll};
+
+    use super::*;
+
+    /// Pending the first `n` times it is polled.
+    struct YieldN(usize);
+
+    impl Future for YieldN {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                Poll::Ready(())
+            } else {
+                self.0 -= 1;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_until_stall() {
+        let mut set = LocalSet::new();
+        // `Rc` is `!Send`, which a `LocalSet` is fine with.
+        let done = Rc::new(Cell::new(0));
+        for yields in 0..3 {
+            let done = done.clone();
+            set.spawn_local(async move {
+                YieldN(yields).await;
+                done.set(done.get() + 1);
+            });
+        }
+        assert_eq!(set.len(), 3);
+
+        set.run_until_stall();
+        assert_eq!(done.get(), 1);
+        assert_eq!(set.len(), 2);
+
+        set.run_until_stall();
+        assert_eq!(done.get(), 2);
+        set.run_until_stall();
+        assert_eq!(done.get(), 3);
+        assert!(set.is_empty());
+
+        // Nothing left to do.
+        set.run_until_stall();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_keeps_order() {
+        let mut set = LocalSet::new();
+        let log = Rc::new(std::cell::RefCell::new(std::vec::Vec::new()));
+        for id in 0..3 {
+            let log = log.clone();
+            set.spawn_local(async move {
+                log.borrow_mut().push((id, 0));
+                YieldN(1).await;
+                log.borrow_mut().push((id, 1));
+            });
+        }
+        set.run_until_stall();
+        set.run_until_stall();
+        assert_eq!(
+            *log.borrow(),
+            [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+        );
+    }
+}
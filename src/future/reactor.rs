@@ -0,0 +1,385 @@
+use std::{
+    io::{ErrorKind, Read, Result, Write},
+    net::{SocketAddr, TcpStream},
+    os::fd::{AsRawFd, RawFd},
+    pin::Pin,
+    sync::Arc,
+    task::{self, Context, Waker},
+    thread::JoinHandle,
+};
+
+use crate::{
+    epoll::{self, Events, Interest, Poll, RawFdSource, Register, Registration, Token},
+    mutex::Mutex,
+    slab::Slab,
+};
+
+// Slab keys count up from zero, so this can't collide with a waiting future.
+const SHUTDOWN: Token = Token(usize::MAX);
+
+/// Drives I/O futures: a background thread blocks in [`Poll::poll`] and wakes
+/// whichever task is waiting for each event.
+///
+/// Clones are handles to the same reactor. The thread stops once the last
+/// handle is dropped, including those inside [`AsyncTcpStream`]s.
+#[derive(Clone)]
+pub struct Reactor {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    register: Register,
+    waiters: Arc<Mutex<Slab<Waiter>>>,
+    shutdown: epoll::Waker,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Waiter {
+    ready: bool,
+    waker: Option<Waker>,
+}
+
+impl Reactor {
+    pub fn new() -> Result<Self> {
+        let poll = Poll::new()?;
+        let shutdown = epoll::Waker::new(&poll, SHUTDOWN)?;
+        let register = poll.register().clone();
+        let waiters = Arc::new(Mutex::new(Slab::new()));
+        let thread = {
+            let waiters = waiters.clone();
+            // The thread keeps its own handle on the eventfd, so the wake
+            // still arrives when `Inner` is dropped on the thread itself.
+            let shutdown = shutdown.clone();
+            std::thread::Builder::new()
+                .name("reactor".into())
+                .spawn(move || {
+                    run(&poll, &waiters);
+                    drop(shutdown);
+                })?
+        };
+        Ok(Self {
+            inner: Arc::new(Inner {
+                register,
+                waiters,
+                shutdown,
+                thread: Some(thread),
+            }),
+        })
+    }
+
+    /// Resolves once `fd` is readable, or has an error or hang-up to report.
+    ///
+    /// This is a hint, not a promise: the read that follows may still hit
+    /// `WouldBlock`, in which case wait again. Only one `readable` or
+    /// `writable` may be pending per fd at a time; another one fails with
+    /// `AlreadyExists`.
+    pub async fn readable(&self, fd: RawFd) -> Result<()> {
+        Readiness::new(self, fd, Interest::READABLE).await
+    }
+
+    /// Like [`readable`](Reactor::readable), for writing.
+    pub async fn writable(&self, fd: RawFd) -> Result<()> {
+        Readiness::new(self, fd, Interest::WRITABLE).await
+    }
+}
+
+fn run(poll: &Poll, waiters: &Mutex<Slab<Waiter>>) {
+    let mut events = Events::with_capacity(64);
+    loop {
+        if let Err(e) = poll.poll(&mut events, None) {
+            panic!("reactor failed to poll: {e}");
+        }
+        let mut woken = Vec::new();
+        let mut waiters = waiters.lock();
+        for event in &events {
+            if event.token() == SHUTDOWN {
+                return;
+            }
+            // The future may have been dropped since its event fired.
+            if let Some(waiter) = waiters.get_mut(event.token().0) {
+                waiter.ready = true;
+                woken.extend(waiter.waker.take());
+            }
+        }
+        drop(waiters);
+        // Outside the lock, since a woken task may be polled on another
+        // thread straight away.
+        woken.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let _ = self.shutdown.wake();
+        let thread = self.thread.take().unwrap();
+        // The last handle can go away on the reactor thread itself, inside a
+        // task dropped by `wake`. It sees the wake on its next poll and
+        // returns by itself then.
+        if thread.thread().id() != std::thread::current().id() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Waits for one event on a fd. The registration is oneshot and made on the
+/// first poll, so each wait costs an `EPOLL_CTL_ADD` and a `DEL`.
+struct Readiness<'a> {
+    reactor: &'a Reactor,
+    fd: RawFd,
+    interest: Interest,
+    registration: Option<(Registration, Token)>,
+}
+
+impl<'a> Readiness<'a> {
+    fn new(reactor: &'a Reactor, fd: RawFd, interest: Interest) -> Self {
+        Self {
+            reactor,
+            fd,
+            interest,
+            registration: None,
+        }
+    }
+}
+
+impl Future for Readiness<'_> {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> task::Poll<Self::Output> {
+        let reactor = self.reactor;
+        let inner = &reactor.inner;
+        let mut waiters = inner.waiters.lock();
+        let Some((_, token)) = self.registration else {
+            let token = Token(waiters.vacant_key());
+            // Registered with the lock held, so the reactor can't look the
+            // token up before the waiter is in place.
+            let registration =
+                match inner
+                    .register
+                    .register(&RawFdSource(self.fd), self.interest.oneshot(), token)
+                {
+                    Ok(registration) => registration,
+                    Err(e) => return task::Poll::Ready(Err(e.into())),
+                };
+            waiters.insert(Waiter {
+                ready: false,
+                waker: Some(cx.waker().clone()),
+            });
+            drop(waiters);
+            self.registration = Some((registration, token));
+            return task::Poll::Pending;
+        };
+        let waiter = &mut waiters[token.0];
+        if waiter.ready {
+            return task::Poll::Ready(Ok(()));
+        }
+        waiter.waker = Some(cx.waker().clone());
+        task::Poll::Pending
+    }
+}
+
+impl Drop for Readiness<'_> {
+    fn drop(&mut self) {
+        if let Some((registration, token)) = self.registration.take() {
+            // Deregister before the token can be handed out again.
+            drop(registration);
+            self.reactor.inner.waiters.lock().remove(token.0);
+        }
+    }
+}
+
+/// A non-blocking `TcpStream` whose reads and writes wait on a [`Reactor`]
+/// instead of blocking the thread.
+pub struct AsyncTcpStream {
+    stream: TcpStream,
+    reactor: Reactor,
+}
+
+impl AsyncTcpStream {
+    pub async fn connect(reactor: &Reactor, addr: SocketAddr) -> Result<Self> {
+        let stream = epoll::connect_nonblocking(addr)?;
+        reactor.writable(stream.as_raw_fd()).await?;
+        // The outcome of the connect is left in `SO_ERROR`.
+        if let Some(e) = stream.take_error()? {
+            return Err(e);
+        }
+        Ok(Self {
+            stream,
+            reactor: reactor.clone(),
+        })
+    }
+
+    /// Reads into `buf`, waiting until some data is there. `Ok(0)` means the
+    /// peer closed its end.
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            match self.stream.read(buf) {
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    self.reactor.readable(self.stream.as_raw_fd()).await?;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Writes some of `buf`, waiting until there is room for at least one
+    /// byte.
+    pub async fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        loop {
+            match self.stream.write(buf) {
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    self.reactor.writable(self.stream.as_raw_fd()).await?;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    pub async fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf).await? {
+                0 => return Err(ErrorKind::WriteZero.into()),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+}
+
+impl AsRawFd for AsyncTcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::future::new_executor_and_spawner;
+
+    #[test]
+    fn test_fetch_from_std_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 5];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(&request, b"fetch");
+            // Larger than a socket buffer, so the client has to wait for
+            // more than one readable event.
+            stream.write_all(&[7; 1 << 20]).unwrap();
+        });
+
+        let reactor = Reactor::new().unwrap();
+        let (executor, spawner) = new_executor_and_spawner();
+        let received = Arc::new(std::sync::Mutex::new(0));
+        let total = received.clone();
+        spawner.spawn(async move {
+            let mut stream = AsyncTcpStream::connect(&reactor, addr).await.unwrap();
+            stream.write_all(b"fetch").await.unwrap();
+            let mut buf = [0; 4096];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                assert!(buf[..n].iter().all(|&b| b == 7));
+                *total.lock().unwrap() += n;
+            }
+        });
+        drop(spawner);
+        executor.run();
+        server.join().unwrap();
+        assert_eq!(*received.lock().unwrap(), 1 << 20);
+    }
+
+    #[test]
+    fn test_connect_refused() {
+        // Bind and drop to get a port nothing listens on.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let reactor = Reactor::new().unwrap();
+        let err = crate::future::block_on(AsyncTcpStream::connect(&reactor, addr))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let reactor = Reactor::new().unwrap();
+        let waiters = Arc::downgrade(&reactor.inner.waiters);
+        let clone = reactor.clone();
+        drop(reactor);
+        assert!(waiters.upgrade().is_some());
+        // Joins the thread, which drops its share of the state on the way
+        // out.
+        drop(clone);
+        assert!(waiters.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_drop_last_handle_on_reactor_thread() {
+        // A waker that cancels its future when woken, which drops the only
+        // `Reactor` handle from inside `run`.
+        struct Cancel(std::sync::Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>);
+
+        impl task::Wake for Cancel {
+            fn wake(self: Arc<Self>) {
+                self.0.lock().unwrap().take();
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let reactor = Reactor::new().unwrap();
+        let waiters = Arc::downgrade(&reactor.inner.waiters);
+        let cancel = Arc::new(Cancel(std::sync::Mutex::new(Some(Box::pin(async move {
+            let _ = reactor.readable(server.as_raw_fd()).await;
+        })))));
+        let waker = Waker::from(cancel.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut future = cancel.0.lock().unwrap().take().unwrap();
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        *cancel.0.lock().unwrap() = Some(future);
+        drop((waker, cancel));
+
+        client.write_all(b"x").unwrap();
+        // The thread drops its share of the state once it has returned.
+        let start = std::time::Instant::now();
+        while waiters.upgrade().is_some() {
+            assert!(
+                start.elapsed() < std::time::Duration::from_secs(5),
+                "reactor thread did not stop"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_one_wait_per_fd() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let reactor = Reactor::new().unwrap();
+        let fd = client.as_raw_fd();
+
+        let mut first = std::pin::pin!(reactor.readable(fd));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        let err = crate::future::block_on(reactor.readable(fd)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
+}
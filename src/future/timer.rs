@@ -13,9 +13,43 @@ pub struct Timer {
 #[derive(Default)]
 pub struct TimerState {
     completed: bool,
+    cancelled: bool,
     waker: Option<Waker>,
 }
 
+/// Returned by a [`Timer`] that was cancelled before it fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timer was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Cancels the [`Timer`] it was created with.
+#[derive(Clone)]
+pub struct CancelHandle {
+    state: Inner,
+}
+
+impl CancelHandle {
+    /// Makes the timer resolve to `Err(Cancelled)` and wakes it. Does
+    /// nothing if the timer has already fired.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.completed {
+            return;
+        }
+        state.cancelled = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
 impl Timer {
     pub fn new(duration: Duration) -> Self {
         let state: Inner = Arc::default();
@@ -23,6 +57,9 @@ impl Timer {
         std::thread::spawn(move || {
             std::thread::sleep(duration);
             let mut state = state_clone.lock().unwrap();
+            if state.cancelled {
+                return;
+            }
             state.completed = true;
             if let Some(waker) = state.waker.take() {
                 waker.wake();
@@ -30,21 +67,66 @@ impl Timer {
         });
         Timer { state }
     }
+
+    /// Like [`Timer::new`], plus a handle that can cancel the timer early.
+    ///
+    /// The sleeping thread isn't interrupted; it just finds the timer
+    /// cancelled when it wakes up.
+    pub fn new_cancellable(duration: Duration) -> (Self, CancelHandle) {
+        let timer = Timer::new(duration);
+        let handle = CancelHandle {
+            state: timer.state.clone(),
+        };
+        (timer, handle)
+    }
 }
 
 impl Future for Timer {
-    type Output = ();
+    type Output = Result<(), Cancelled>;
 
     fn poll(
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
         let mut state = self.state.lock().unwrap();
-        if state.completed {
-            Poll::Ready(())
+        if state.cancelled {
+            Poll::Ready(Err(Cancelled))
+        } else if state.completed {
+            Poll::Ready(Ok(()))
         } else {
             state.waker = Some(cx.waker().clone());
             Poll::Pending
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::future::block_on;
+
+    #[test]
+    fn test_cancel() {
+        let start = Instant::now();
+        let (timer, handle) = Timer::new_cancellable(Duration::from_secs(10));
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            handle.cancel();
+        });
+
+        assert_eq!(block_on(timer), Err(Cancelled));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(5), "took {elapsed:?}");
+    }
+
+    #[test]
+    fn test_cancel_after_fire() {
+        let (timer, handle) = Timer::new_cancellable(Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+        assert_eq!(block_on(timer), Ok(()));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::vec::Vec;
+
+/// A max-heap: `peek` and `pop` return the greatest element.
+pub struct BinaryHeap<T: Ord> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Builds a heap in O(n) by sifting down every internal node, starting
+    /// from the last one (Floyd's method).
+    pub fn from_vec(data: Vec<T>) -> Self {
+        let mut heap = Self { data };
+        for i in (0..heap.len() / 2).rev() {
+            heap.sift_down(i);
+        }
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        self.sift_up(self.len() - 1);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.len().checked_sub(1)?;
+        self.data.swap(0, last);
+        let value = self.data.pop();
+        if !self.is_empty() {
+            self.sift_down(0);
+        }
+        value
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.data[index] <= self.data[parent] {
+                break;
+            }
+            self.data.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        let len = self.len();
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut largest = index;
+            if left < len && self.data[left] > self.data[largest] {
+                largest = left;
+            }
+            if right < len && self.data[right] > self.data[largest] {
+                largest = right;
+            }
+            if largest == index {
+                break;
+            }
+            self.data.swap(index, largest);
+            index = largest;
+        }
+    }
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut data = Vec::new();
+        data.extend(iter);
+        Self::from_vec(data)
+    }
+}
+
+/// Yields the elements from greatest to smallest.
+pub struct IntoIter<T: Ord> {
+    heap: BinaryHeap<T>,
+}
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Ord> IntoIterator for BinaryHeap<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { heap: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = BinaryHeap::new();
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+
+        for v in [3, 1, 4, 1, 5, 9, 2, 6] {
+            heap.push(v);
+        }
+        assert_eq!(heap.len(), 8);
+        assert_eq!(heap.peek(), Some(&9));
+
+        assert_eq!(heap.pop(), Some(9));
+        assert_eq!(heap.pop(), Some(6));
+        assert_eq!(heap.pop(), Some(5));
+        assert_eq!(heap.len(), 5);
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut data = Vec::new();
+        data.extend((0..100).map(|i| (i * 37) % 100));
+        let heap = BinaryHeap::from_vec(data);
+        assert_eq!(heap.peek(), Some(&99));
+
+        let sorted: std::vec::Vec<_> = heap.into_iter().collect();
+        assert_eq!(sorted, (0..100).rev().collect::<std::vec::Vec<_>>());
+    }
+
+    #[test]
+    fn test_into_iter_sorted() {
+        let heap: BinaryHeap<_> = ["pear", "apple", "fig", "kiwi"].into_iter().collect();
+        let mut iter = heap.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some("pear"));
+        assert_eq!(iter.next(), Some("kiwi"));
+        assert_eq!(iter.next(), Some("fig"));
+        assert_eq!(iter.next(), Some("apple"));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let heap: BinaryHeap<_> = [2, 2, 1, 2].into_iter().collect();
+        assert_eq!(heap.into_iter().collect::<std::vec::Vec<_>>(), [2, 2, 2, 1]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::{marker::PhantomData, ptr};
+
+/// Link fields embedded in a node. Both pointers are null while the node is
+/// not in a list.
+pub struct IntrusiveLinks {
+    prev: *mut (),
+    next: *mut (),
+}
+
+impl IntrusiveLinks {
+    pub const fn new() -> Self {
+        Self {
+            prev: ptr::null_mut(),
+            next: ptr::null_mut(),
+        }
+    }
+}
+
+impl Default for IntrusiveLinks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A type that carries its own [`IntrusiveLinks`].
+///
+/// # Safety
+///
+/// `links` and `links_mut` must always return the same field of `self`, and
+/// must not replace or otherwise modify it. The list follows the pointers
+/// stored there without checking them.
+pub unsafe trait IntrusiveNode {
+    fn links(&self) -> &IntrusiveLinks;
+    fn links_mut(&mut self) -> &mut IntrusiveLinks;
+}
+
+/// A doubly-linked list over nodes that live in caller-owned memory.
+///
+/// The list never allocates: it only rewrites the links inside the nodes.
+/// It holds the `&'a mut` borrow of every node it contains, so a node can't
+/// be moved, freed or touched elsewhere while linked, and popping hands the
+/// borrow back.
+pub struct IntrusiveList<'a, T: IntrusiveNode> {
+    head: *mut T,
+    tail: *mut T,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: IntrusiveNode> IntrusiveList<'a, T> {
+    pub const fn new() -> Self {
+        Self {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_back(&mut self, node: &'a mut T) {
+        let node: *mut T = node;
+        unsafe {
+            let links = (*node).links_mut();
+            links.prev = self.tail.cast();
+            links.next = ptr::null_mut();
+            if self.tail.is_null() {
+                self.head = node;
+            } else {
+                (*self.tail).links_mut().next = node.cast();
+            }
+        }
+        self.tail = node;
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, node: &'a mut T) {
+        let node: *mut T = node;
+        unsafe {
+            let links = (*node).links_mut();
+            links.prev = ptr::null_mut();
+            links.next = self.head.cast();
+            if self.head.is_null() {
+                self.tail = node;
+            } else {
+                (*self.head).links_mut().prev = node.cast();
+            }
+        }
+        self.head = node;
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<&'a mut T> {
+        if self.head.is_null() {
+            return None;
+        }
+        unsafe {
+            let node = &mut *self.head;
+            let links = node.links_mut();
+            self.head = links.next.cast();
+            links.next = ptr::null_mut();
+            if self.head.is_null() {
+                self.tail = ptr::null_mut();
+            } else {
+                (*self.head).links_mut().prev = ptr::null_mut();
+            }
+            self.len -= 1;
+            Some(node)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<&'a mut T> {
+        if self.tail.is_null() {
+            return None;
+        }
+        unsafe {
+            let node = &mut *self.tail;
+            let links = node.links_mut();
+            self.tail = links.prev.cast();
+            links.prev = ptr::null_mut();
+            if self.tail.is_null() {
+                self.head = ptr::null_mut();
+            } else {
+                (*self.tail).links_mut().next = ptr::null_mut();
+            }
+            self.len -= 1;
+            Some(node)
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.head.as_ref() }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.tail.as_ref() }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: IntrusiveNode> Default for IntrusiveList<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: *mut T,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: IntrusiveNode> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.next.as_ref()? };
+        self.next = node.links().next.cast();
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Task {
+        id: u32,
+        links: IntrusiveLinks,
+    }
+
+    impl Task {
+        fn new(id: u32) -> Self {
+            Self {
+                id,
+                links: IntrusiveLinks::new(),
+            }
+        }
+    }
+
+    // SAFETY: both methods return the `links` field, untouched.
+    unsafe impl IntrusiveNode for Task {
+        fn links(&self) -> &IntrusiveLinks {
+            &self.links
+        }
+
+        fn links_mut(&mut self) -> &mut IntrusiveLinks {
+            &mut self.links
+        }
+    }
+
+    fn ids(list: &IntrusiveList<'_, Task>) -> std::vec::Vec<u32> {
+        list.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut a = Task::new(1);
+        let mut b = Task::new(2);
+        let mut c = Task::new(3);
+
+        let mut list = IntrusiveList::new();
+        assert!(list.pop_front().is_none());
+        list.push_back(&mut b);
+        list.push_back(&mut c);
+        list.push_front(&mut a);
+        assert_eq!(list.len(), 3);
+        assert_eq!(ids(&list), [1, 2, 3]);
+        assert_eq!(list.front().map(|t| t.id), Some(1));
+        assert_eq!(list.back().map(|t| t.id), Some(3));
+
+        let first = list.pop_front().unwrap();
+        assert_eq!(first.id, 1);
+        assert!(first.links.next.is_null() && first.links.prev.is_null());
+        first.id = 10;
+
+        assert_eq!(list.pop_back().map(|t| t.id), Some(3));
+        assert_eq!(list.pop_back().map(|t| t.id), Some(2));
+        assert!(list.is_empty());
+        assert!(list.back().is_none());
+
+        // The nodes are plain values again once the list is no longer used.
+        assert_eq!(a.id, 10);
+    }
+
+    #[test]
+    fn test_relink_popped_node() {
+        let mut nodes: std::vec::Vec<Task> = (0..4).map(Task::new).collect();
+        let mut list = IntrusiveList::new();
+        for node in nodes.iter_mut() {
+            list.push_back(node);
+        }
+
+        // Rotate: move the front node to the back without allocating.
+        let node = list.pop_front().unwrap();
+        list.push_back(node);
+        assert_eq!(ids(&list), [1, 2, 3, 0]);
+
+        while list.pop_front().is_some() {}
+        assert_eq!(nodes.len(), 4);
+    }
+}
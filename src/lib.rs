@@ -1,16 +1,32 @@
 #![feature(dropck_eyepatch)]
 
 pub mod arc;
+pub mod arena;
+pub mod barrier;
 pub mod r#box;
 pub mod cell;
 pub mod cond_var;
 pub mod epoll;
 pub mod future;
+pub mod heap;
+pub mod intrusive;
 pub mod link;
+pub mod lru;
+pub mod mpmc;
 pub mod mutex;
+pub mod once;
 pub mod one_shot;
+pub mod queue;
 pub mod rc;
+pub mod ring_buffer;
 pub mod rw_lock;
 pub mod safe;
+pub mod seqlock;
+pub mod skiplist;
+pub mod slab;
+pub mod small_vec;
 pub mod spinlock;
+pub mod stack;
+pub mod thread_local;
+pub mod trie;
 pub mod vec;
@@ -1,5 +1,9 @@
 use core::ptr::NonNull;
-use std::{fmt::Debug, hash::Hash};
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    iter::FusedIterator,
+};
 
 pub struct List<T> {
     head: Link<T>,
@@ -10,10 +14,10 @@ pub struct List<T> {
 
 type Link<T> = Option<NonNull<Node<T>>>;
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     front: Link<T>,
     back: Link<T>,
-    elem: T,
+    pub(crate) elem: T,
 }
 
 impl<T> List<T> {
@@ -138,6 +142,65 @@ impl<T> List<T> {
         self.into_iter()
     }
 
+    /// Iterates from the element at `index` to the end of the list.
+    ///
+    /// Reaching `index` walks from whichever end is closer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn iter_from(&self, index: usize) -> Iter<'_, T> {
+        self.iter_range(index, self.len)
+    }
+
+    /// Iterates over the elements in `start..end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or `end > len`.
+    pub fn iter_range(&self, start: usize, end: usize) -> Iter<'_, T> {
+        assert!(start <= end, "range start {start} is after end {end}");
+        assert!(
+            end <= self.len,
+            "range end {end} is out of bounds for length {}",
+            self.len
+        );
+        if start == end {
+            return Iter {
+                front: None,
+                back: None,
+                len: 0,
+                _marker: std::marker::PhantomData,
+            };
+        }
+        Iter {
+            front: self.node_at(start),
+            back: self.node_at(end - 1),
+            len: end - start,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    // Walks to the node at `index` from whichever end is closer.
+    fn node_at(&self, index: usize) -> Link<T> {
+        debug_assert!(index < self.len);
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.head;
+                for _ in 0..index {
+                    node = (*node?.as_ptr()).back;
+                }
+                node
+            } else {
+                let mut node = self.tail;
+                for _ in index + 1..self.len {
+                    node = (*node?.as_ptr()).front;
+                }
+                node
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         while self.pop_front().is_some() {
             // Continuously pop elements until the list is empty
@@ -151,6 +214,61 @@ impl<T> List<T> {
             index: None,
         }
     }
+
+    // Node-handle operations for structures that index into the list, such as
+    // `LruCache`. A handle stays valid until its node is unlinked or popped.
+
+    pub(crate) fn push_front_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        self.push_front(elem);
+        self.head.unwrap()
+    }
+
+    /// # Safety
+    ///
+    /// `node` must be a live node of this list.
+    pub(crate) unsafe fn unlink_node(&mut self, node: NonNull<Node<T>>) -> T {
+        unsafe {
+            self.detach(node);
+            Box::from_raw(node.as_ptr()).elem
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `node` must be a live node of this list.
+    pub(crate) unsafe fn move_node_to_front(&mut self, node: NonNull<Node<T>>) {
+        if self.head == Some(node) {
+            return;
+        }
+        unsafe {
+            self.detach(node);
+            (*node.as_ptr()).back = self.head;
+            if let Some(old_head) = self.head {
+                (*old_head.as_ptr()).front = Some(node);
+            } else {
+                self.tail = Some(node);
+            }
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    // Unlinks `node` from its neighbours without freeing it.
+    unsafe fn detach(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            let front = (*node.as_ptr()).front.take();
+            let back = (*node.as_ptr()).back.take();
+            match front {
+                Some(front) => (*front.as_ptr()).back = back,
+                None => self.head = back,
+            }
+            match back {
+                Some(back) => (*back.as_ptr()).front = front,
+                None => self.tail = front,
+            }
+        }
+        self.len -= 1;
+    }
 }
 
 impl<'a, T> IntoIterator for &'a List<T> {
@@ -239,6 +357,20 @@ impl<T: Debug> Debug for List<T> {
     }
 }
 
+/// Formats as `[a, b, c]`, using each element's `Display`.
+impl<T: Display> Display for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
 impl<T: PartialEq> PartialEq for List<T> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other.iter())
@@ -323,6 +455,8 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
 pub struct IterMut<'a, T> {
     front: Link<T>,
     back: Link<T>,
@@ -370,6 +504,8 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
 pub struct IntoIter<T> {
     list: List<T>,
 }
@@ -398,6 +534,8 @@ impl<T> ExactSizeIterator for IntoIter<T> {
     }
 }
 
+impl<T> FusedIterator for IntoIter<T> {}
+
 pub struct CursorMut<'a, T> {
     cur: Link<T>,
     list: &'a mut List<T>,
@@ -438,6 +576,40 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
+    /// Moves to the next element unless that would step off the end onto the
+    /// ghost position. Returns whether the cursor moved; at the tail, or in an
+    /// empty list, it stays where it is.
+    ///
+    /// From the ghost this moves to the head, like [`move_next`](Self::move_next).
+    pub fn try_move_next(&mut self) -> bool {
+        let next = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).back },
+            None => self.list.head,
+        };
+        if next.is_none() {
+            return false;
+        }
+        self.move_next();
+        true
+    }
+
+    /// Moves to the previous element unless that would step off the front
+    /// onto the ghost position. Returns whether the cursor moved; at the
+    /// head, or in an empty list, it stays where it is.
+    ///
+    /// From the ghost this moves to the tail, like [`move_prev`](Self::move_prev).
+    pub fn try_move_prev(&mut self) -> bool {
+        let prev = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).front },
+            None => self.list.tail,
+        };
+        if prev.is_none() {
+            return false;
+        }
+        self.move_prev();
+        true
+    }
+
     pub fn current(&mut self) -> Option<&mut T> {
         self.cur.map(|mut node| unsafe { &mut node.as_mut().elem })
     }
@@ -903,6 +1075,29 @@ mod tests {
         assert_eq!(iter_mut.next(), None);
     }
 
+    #[test]
+    fn test_fused() {
+        fn is_fused<I: FusedIterator>(_: &I) {}
+
+        let mut list: List<i32> = (0..2).collect();
+        let mut iter = list.iter();
+        is_fused(&iter);
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let mut iter = list.iter_mut();
+        is_fused(&iter);
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list.into_iter();
+        is_fused(&iter);
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
     #[test]
     fn test_list_clone() {
         let mut list = List::new();
@@ -1151,6 +1346,36 @@ mod tests {
         assert_eq!(cursor.index(), Some(4));
     }
 
+    #[test]
+    fn test_cursor_try_move() {
+        let mut m: List<u32> = List::new();
+        let mut cursor = m.cursor_mut();
+        assert!(!cursor.try_move_next());
+        assert!(!cursor.try_move_prev());
+        assert_eq!(cursor.index(), None);
+
+        m.extend([1, 2, 3]);
+        let mut cursor = m.cursor_mut();
+        let mut seen = Vec::new();
+        while cursor.try_move_next() {
+            seen.push(*cursor.current().unwrap());
+        }
+        assert_eq!(seen, [1, 2, 3]);
+        // Stopped at the tail instead of wrapping to the ghost.
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+
+        while cursor.try_move_prev() {}
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        let mut cursor = m.cursor_mut();
+        assert!(cursor.try_move_prev());
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert!(!cursor.try_move_next());
+        assert_eq!(cursor.index(), Some(2));
+    }
+
     #[test]
     fn test_cursor_mut_insert() {
         let mut m: List<u32> = List::new();
@@ -1241,4 +1466,38 @@ mod tests {
 
         assert_eq!(from_front, re_reved);
     }
+
+    #[test]
+    fn test_display() {
+        let list: List<i32> = (1..=3).collect();
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(List::<String>::new().to_string(), "[]");
+        // Format options apply to every element.
+        assert_eq!(format!("{list:>2}"), "[ 1,  2,  3]");
+    }
+
+    #[test]
+    fn test_iter_from_range() {
+        let list: List<i32> = (0..10).collect();
+        assert!(list.iter_from(0).eq(list.iter()));
+        assert!(list.iter_from(3).eq(&[3, 4, 5, 6, 7, 8, 9]));
+        // Past the middle the walk starts at the tail.
+        assert!(list.iter_from(8).eq(&[8, 9]));
+        assert_eq!(list.iter_from(10).next(), None);
+
+        assert!(list.iter_range(2, 5).eq(&[2, 3, 4]));
+        assert!(list.iter_range(2, 5).rev().eq(&[4, 3, 2]));
+        assert_eq!(list.iter_range(7, 9).len(), 2);
+        assert_eq!(list.iter_range(4, 4).next_back(), None);
+
+        let empty = List::<i32>::new();
+        assert_eq!(empty.iter_from(0).next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_iter_from_out_of_bounds() {
+        let list: List<i32> = (0..3).collect();
+        list.iter_from(4);
+    }
 }
@@ -0,0 +1,159 @@
+use std::{collections::HashMap, hash::Hash, ptr::NonNull};
+
+use crate::link::{List, Node};
+
+/// A least-recently-used cache with O(1) `get`, `insert` and `remove`.
+///
+/// Entries live in a [`List`] ordered from most to least recently used, and
+/// the map points straight at each entry's node so it can be moved or
+/// unlinked without walking the list.
+pub struct LruCache<K: Hash + Eq + Clone, V> {
+    map: HashMap<K, NonNull<Node<(K, V)>>>,
+    list: List<(K, V)>,
+    cap: usize,
+}
+
+unsafe impl<K: Hash + Eq + Clone + Send, V: Send> Send for LruCache<K, V> {}
+unsafe impl<K: Hash + Eq + Clone + Sync, V: Sync> Sync for LruCache<K, V> {}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(cap),
+            list: List::new(),
+            cap,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the value without marking it as recently used.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let node = self.map.get(key)?;
+        Some(unsafe { &node.as_ref().elem.1 })
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let node = *self.map.get(key)?;
+        unsafe {
+            self.list.move_node_to_front(node);
+            Some(&node.as_ref().elem.1)
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = *self.map.get(key)?;
+        unsafe {
+            self.list.move_node_to_front(node);
+            Some(&mut node.as_mut().elem.1)
+        }
+    }
+
+    /// Inserts or updates `key`, marking it as most recently used, and
+    /// returns the previous value if there was one. Inserting a new key into
+    /// a full cache evicts the least recently used entry.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&mut mut node) = self.map.get_mut(&key) {
+            unsafe {
+                self.list.move_node_to_front(node);
+                return Some(std::mem::replace(&mut node.as_mut().elem.1, value));
+            }
+        }
+
+        let node = self.list.push_front_node((key.clone(), value));
+        self.map.insert(key, node);
+        if self.list.len() > self.cap
+            && let Some((evicted, _)) = self.list.pop_back()
+        {
+            self.map.remove(&evicted);
+        }
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let node = self.map.remove(key)?;
+        let (_, value) = unsafe { self.list.unlink_node(node) };
+        Some(value)
+    }
+
+    /// Iterates from most to least recently used.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.list.iter().map(|(k, v)| (k, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_order() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.insert("a", 1), None);
+        assert_eq!(cache.insert("b", 2), None);
+
+        // Touch "a" so "b" becomes least recently used.
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 3);
+        assert!(!cache.contains(&"b"));
+        assert_eq!(cache.len(), 2);
+
+        cache.insert("d", 4);
+        assert!(!cache.contains(&"a"));
+        assert_eq!(cache.peek(&"c"), Some(&3));
+        assert_eq!(cache.peek(&"d"), Some(&4));
+    }
+
+    #[test]
+    fn test_update_and_remove() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, String::from("one"));
+        cache.insert(2, String::from("two"));
+        assert_eq!(cache.insert(1, String::from("uno")).as_deref(), Some("one"));
+
+        // Updating refreshes the entry, so 2 is evicted next.
+        cache.insert(3, String::from("three"));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1).map(String::as_str), Some("uno"));
+
+        cache.get_mut(&3).unwrap().push('!');
+        assert_eq!(cache.remove(&3).as_deref(), Some("three!"));
+        assert_eq!(cache.remove(&3), None);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_iter_order() {
+        let mut cache = LruCache::new(3);
+        cache.insert(1, 'a');
+        cache.insert(2, 'b');
+        cache.insert(3, 'c');
+        cache.get(&1);
+        cache.remove(&2);
+
+        let keys: Vec<_> = cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, [1, 3]);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut cache = LruCache::new(0);
+        cache.insert(1, 1);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&1), None);
+    }
+}
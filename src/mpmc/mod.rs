@@ -0,0 +1,331 @@
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+// Keeps hot atomics on separate cache lines so producers and consumers do not
+// invalidate each other's line on every operation.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// A slot is writable at position `pos` when `sequence == pos`, and readable
+// once the producer bumps it to `pos + 1`. The consumer then sets it to
+// `pos + capacity`, i.e. the position at which it is writable again.
+struct Slot<T> {
+    sequence: AtomicU64,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Channel<T> {
+    buffer: Box<[CachePadded<Slot<T>>]>,
+    mask: u64,
+    head: CachePadded<AtomicU64>,
+    tail: CachePadded<AtomicU64>,
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+}
+
+unsafe impl<T: Send> Send for Channel<T> {}
+unsafe impl<T: Send> Sync for Channel<T> {}
+
+impl<T> Channel<T> {
+    fn with_capacity(cap: usize) -> Self {
+        // The sequence scheme cannot tell "full" from "empty" with a single slot.
+        let cap = cap.max(2).next_power_of_two();
+        let buffer = (0..cap as u64)
+            .map(|i| {
+                CachePadded(Slot {
+                    sequence: AtomicU64::new(i),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+            })
+            .collect();
+        Self {
+            buffer,
+            mask: cap as u64 - 1,
+            head: CachePadded(AtomicU64::new(0)),
+            tail: CachePadded(AtomicU64::new(0)),
+            senders: AtomicUsize::new(1),
+            receivers: AtomicUsize::new(1),
+        }
+    }
+
+    fn try_push(&self, value: T) -> Result<(), T> {
+        let mut pos = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[(pos & self.mask) as usize];
+            let seq = slot.sequence.load(Ordering::Acquire);
+            let diff = seq as i64 - pos as i64;
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    pos,
+                    pos + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence.store(pos + 1, Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(actual) => pos = actual,
+                }
+            } else if diff < 0 {
+                return Err(value);
+            } else {
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        let mut pos = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[(pos & self.mask) as usize];
+            let seq = slot.sequence.load(Ordering::Acquire);
+            let diff = seq as i64 - (pos + 1) as i64;
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    pos,
+                    pos + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.sequence.store(pos + self.mask + 1, Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(actual) => pos = actual,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        while self.try_pop().is_some() {}
+    }
+}
+
+fn backoff(step: &mut u32) {
+    if *step < 6 {
+        for _ in 0..1 << *step {
+            std::hint::spin_loop();
+        }
+        *step += 1;
+    } else {
+        std::thread::yield_now();
+    }
+}
+
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        self.channel.try_push(value)
+    }
+
+    /// Sends `value`, waiting while the channel is full.
+    ///
+    /// Returns the value back if every receiver has been dropped.
+    pub fn send(&self, mut value: T) -> Result<(), T> {
+        let mut step = 0;
+        loop {
+            if self.channel.receivers.load(Ordering::Relaxed) == 0 {
+                return Err(value);
+            }
+            match self.channel.try_push(value) {
+                Ok(()) => return Ok(()),
+                Err(v) => value = v,
+            }
+            backoff(&mut step);
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.senders.fetch_sub(1, Ordering::Release);
+    }
+}
+
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Option<T> {
+        self.channel.try_pop()
+    }
+
+    /// Receives a value, waiting while the channel is empty.
+    ///
+    /// Returns `None` once the channel is empty and every sender is gone.
+    pub fn recv(&self) -> Option<T> {
+        let mut step = 0;
+        loop {
+            if let Some(value) = self.channel.try_pop() {
+                return Some(value);
+            }
+            if self.channel.senders.load(Ordering::Acquire) == 0 {
+                // A last send may have landed between the pop and the load.
+                return self.channel.try_pop();
+            }
+            backoff(&mut step);
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.channel.receivers.fetch_add(1, Ordering::Relaxed);
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.channel.receivers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Creates a bounded channel. `cap` is rounded up to a power of two (at
+/// least 2) so positions can be mapped to slots with a mask.
+pub fn channel<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel::with_capacity(cap));
+    (
+        Sender {
+            channel: channel.clone(),
+        },
+        Receiver { channel },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_thread() {
+        let (tx, rx) = channel(4);
+        for i in 0..4 {
+            tx.try_send(i).unwrap();
+        }
+        assert_eq!(tx.try_send(4), Err(4));
+
+        assert_eq!(rx.try_recv(), Some(0));
+        tx.try_send(4).unwrap();
+        for i in 1..5 {
+            assert_eq!(rx.recv(), Some(i));
+        }
+        assert_eq!(rx.try_recv(), None);
+
+        drop(tx);
+        assert_eq!(rx.recv(), None);
+    }
+
+    #[test]
+    fn test_capacity_rounding() {
+        let (tx, _rx) = channel(3);
+        for i in 0..4 {
+            tx.try_send(i).unwrap();
+        }
+        assert_eq!(tx.try_send(4), Err(4));
+
+        let (tx, _rx) = channel(0);
+        tx.try_send(0).unwrap();
+        tx.try_send(1).unwrap();
+        assert_eq!(tx.try_send(2), Err(2));
+    }
+
+    #[test]
+    fn test_send_disconnected() {
+        let (tx, rx) = channel(2);
+        drop(rx);
+        assert_eq!(tx.send(1), Err(1));
+    }
+
+    #[test]
+    fn test_drop_unreceived() {
+        let (tx, rx) = channel(4);
+        let value = Arc::new(());
+        tx.send(value.clone()).unwrap();
+        tx.send(value.clone()).unwrap();
+        drop((tx, rx));
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_mpmc() {
+        const PRODUCERS: u64 = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: u64 = 100_000;
+
+        let (tx, rx) = channel(1024);
+
+        let results: Vec<_> = std::thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let tx = tx.clone();
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        tx.send(p * PER_PRODUCER + i).unwrap();
+                    }
+                });
+            }
+            drop(tx);
+
+            let handles: Vec<_> = (0..CONSUMERS)
+                .map(|_| {
+                    let rx = rx.clone();
+                    s.spawn(move || {
+                        let (mut count, mut sum) = (0u64, 0u64);
+                        while let Some(v) = rx.recv() {
+                            count += 1;
+                            sum += v;
+                        }
+                        (count, sum)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let total = PRODUCERS * PER_PRODUCER;
+        let count: u64 = results.iter().map(|r| r.0).sum();
+        let sum: u64 = results.iter().map(|r| r.1).sum();
+        assert_eq!(count, total);
+        assert_eq!(sum, total * (total - 1) / 2);
+    }
+}
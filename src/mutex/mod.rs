@@ -1,37 +1,114 @@
 use std::{
     cell::UnsafeCell,
+    fmt::{Debug, Display},
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicU32, Ordering},
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
 };
 
 use atomic_wait::{wait, wake_one};
 
+use crate::cond_var::Condvar;
+
+/// A futex-based mutex.
+///
+/// Poisoning works as for [`SpinLock`](crate::spinlock::SpinLock): dropping
+/// a guard during a panic poisons the mutex, and from then on `lock`,
+/// `try_lock` and [`Condvar::wait`] panic until `clear_poison` is called.
 pub struct Mutex<T> {
     locked: AtomicU32,
+    poisoned: AtomicBool,
     data: UnsafeCell<T>,
 }
 
 pub struct MutexGuard<'a, T> {
     pub(crate) lock: &'a Mutex<T>,
+    // A guard taken while already unwinding must not poison the mutex.
+    panicking: bool,
+    // Keeps the guard on the thread that locked the mutex, unlike the
+    // spinlock and `RwLock` guards. The futex itself could be released from
+    // any thread, but `panicking` is a snapshot of the locking thread's
+    // state: dropped on another thread, the guard would decide whether to
+    // poison from two different threads' states. Checked by the doctests on
+    // `_guard_auto_traits`.
     _marker: std::marker::PhantomData<*mut T>,
 }
 
+impl<'a, T> MutexGuard<'a, T> {
+    fn new(lock: &'a Mutex<T>) -> Self {
+        let guard = Self::new_unchecked(lock);
+        // Built first so the panic below still unlocks.
+        if lock.is_poisoned() {
+            panic!("Mutex poisoned: a thread panicked while holding it");
+        }
+        guard
+    }
+
+    fn new_unchecked(lock: &'a Mutex<T>) -> Self {
+        Self {
+            lock,
+            panicking: std::thread::panicking(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Releases the lock. The same as `drop(guard)`, but harder to miss when
+    /// reading the code.
+    pub fn unlock(guard: Self) {
+        drop(guard);
+    }
+}
+
 unsafe impl<T: Send> Sync for Mutex<T> {}
 
+/// A `MutexGuard` is neither `Send` nor `Sync`, whatever `T` is, so it
+/// stays on the thread that locked the mutex.
+///
+/// ```compile_fail
+/// use learn_unsafe::mutex::Mutex;
+/// fn is_send<T: Send>(_: T) {}
+/// let mutex = Mutex::new(1);
+/// is_send(mutex.lock());
+/// ```
+///
+/// ```compile_fail
+/// use learn_unsafe::mutex::Mutex;
+/// fn is_sync<T: Sync>(_: T) {}
+/// let mutex = Mutex::new(1);
+/// is_sync(mutex.lock());
+/// ```
+fn _guard_auto_traits() {}
+
 impl<T> Mutex<T> {
     pub const fn new(data: T) -> Self {
         Mutex {
             locked: AtomicU32::new(0),
+            poisoned: AtomicBool::new(false),
             data: UnsafeCell::new(data),
         }
     }
 
     pub fn lock(&self) -> MutexGuard<'_, T> {
         lock_contended(&self.locked);
-        MutexGuard {
-            lock: self,
-            _marker: std::marker::PhantomData,
-        }
+        MutexGuard::new(self)
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.try_acquire().then(|| MutexGuard::new(self))
+    }
+
+    fn try_acquire(&self) -> bool {
+        self.locked
+            .compare_exchange(0, 1, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Marks the data as consistent again so `lock` stops panicking.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
     }
 
     pub fn with_fn<F, R>(&self, f: F) -> R
@@ -41,6 +118,21 @@ impl<T> Mutex<T> {
         let mut lock = self.lock();
         f(&mut *lock)
     }
+
+    /// Locks, waits on `condvar` until `ready` returns true, then runs `f`
+    /// with the lock still held: the usual lock/wait/check/act loop in one
+    /// call. Whoever makes `ready` true must notify `condvar`.
+    pub fn with_fn_wait<P, F, R>(&self, condvar: &Condvar, mut ready: P, f: F) -> R
+    where
+        P: FnMut(&mut T) -> bool,
+        F: FnOnce(&mut T) -> R,
+    {
+        let mut lock = self.lock();
+        while !ready(&mut *lock) {
+            lock = condvar.wait(lock);
+        }
+        f(&mut *lock)
+    }
 }
 
 fn lock_contended(state: &AtomicU32) {
@@ -73,8 +165,39 @@ impl<T> DerefMut for MutexGuard<'_, T> {
     }
 }
 
+impl<T: Debug> Debug for Mutex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        // Not `try_lock`, which panics on a poisoned mutex.
+        if self.try_acquire() {
+            let guard = MutexGuard::new_unchecked(self);
+            d.field("data", &&*guard);
+        } else {
+            d.field("data", &format_args!("<locked>"));
+        }
+        d.field("poisoned", &self.is_poisoned());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug> Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
 impl<T> Drop for MutexGuard<'_, T> {
     fn drop(&mut self) {
+        if !self.panicking && std::thread::panicking() {
+            // Published by the Release swap below.
+            self.lock.poisoned.store(true, Ordering::Relaxed);
+        }
         if self.lock.locked.swap(0, Ordering::Release) == 2 {
             wake_one(&self.lock.locked);
         }
@@ -135,6 +258,48 @@ mod tests {
         assert_eq!(*guard, 100000); // Check the final value after high contention
     }
 
+    #[test]
+    fn test_try_lock() {
+        let mutex = Mutex::new(1);
+        let guard = mutex.try_lock().unwrap();
+        assert!(mutex.try_lock().is_none());
+        drop(guard);
+        *mutex.try_lock().unwrap() += 1;
+        assert_eq!(*mutex.lock(), 2);
+    }
+
+    #[test]
+    fn test_unlock() {
+        let mutex = Mutex::new(1);
+        let mut guard = mutex.lock();
+        *guard += 1;
+        MutexGuard::unlock(guard);
+        assert_eq!(*mutex.try_lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_debug() {
+        #[derive(Debug)]
+        struct Shared {
+            _counter: Mutex<u32>,
+        }
+
+        let shared = Shared {
+            _counter: Mutex::new(7),
+        };
+        assert_eq!(
+            format!("{shared:?}"),
+            "Shared { _counter: Mutex { data: 7, poisoned: false, .. } }"
+        );
+
+        let guard = shared._counter.lock();
+        assert_eq!(format!("{guard:?} {guard}"), "7 7");
+        assert_eq!(
+            format!("{:?}", shared._counter),
+            "Mutex { data: <locked>, poisoned: false, .. }"
+        );
+    }
+
     #[test]
     fn test_mutex_with_fn() {
         let mutex = Mutex::new(vec![]);
@@ -155,4 +320,23 @@ mod tests {
             assert_eq!(data[2], 3);
         });
     }
+
+    #[test]
+    fn test_poison() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mutex = Mutex::new(0);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut guard = mutex.lock();
+            *guard = 1;
+            panic!("mid-update");
+        }));
+        assert!(result.is_err());
+        assert!(mutex.is_poisoned());
+        assert!(format!("{mutex:?}").contains("poisoned: true"));
+
+        assert!(catch_unwind(AssertUnwindSafe(|| mutex.with_fn(|_| ()))).is_err());
+        mutex.clear_poison();
+        assert_eq!(*mutex.lock(), 1);
+    }
 }
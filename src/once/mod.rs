@@ -0,0 +1,319 @@
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use atomic_wait::{wait, wake_all};
+
+const INCOMPLETE: u32 = 0;
+const RUNNING: u32 = 1;
+const COMPLETE: u32 = 2;
+const POISONED: u32 = 3;
+
+pub struct Once {
+    state: AtomicU32,
+}
+
+impl Once {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU32::new(INCOMPLETE),
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    /// Runs `f` exactly once, even if called from several threads at the same
+    /// time. Callers that lose the race block until `f` has finished.
+    ///
+    /// If `f` panics the `Once` becomes poisoned and every later call panics.
+    pub fn call_once(&self, f: impl FnOnce()) {
+        self.call(f, "Once");
+    }
+
+    /// [`call_once`](Once::call_once), naming `owner` in the poison panic so
+    /// the types built on top report themselves.
+    fn call(&self, f: impl FnOnce(), owner: &str) {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            match state {
+                COMPLETE => return,
+                POISONED => panic!("{owner} instance has previously been poisoned"),
+                INCOMPLETE => {
+                    if let Err(e) = self.state.compare_exchange(
+                        INCOMPLETE,
+                        RUNNING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        state = e;
+                        continue;
+                    }
+                    // If `f` unwinds, the guard is dropped with `set_to`
+                    // still POISONED.
+                    let mut guard = CompletionGuard {
+                        state: &self.state,
+                        set_to: POISONED,
+                    };
+                    f();
+                    guard.set_to = COMPLETE;
+                    return;
+                }
+                _ => {
+                    wait(&self.state, RUNNING);
+                    state = self.state.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct CompletionGuard<'a> {
+    state: &'a AtomicU32,
+    set_to: u32,
+}
+
+impl Drop for CompletionGuard<'_> {
+    fn drop(&mut self) {
+        self.state.store(self.set_to, Ordering::Release);
+        wake_all(self.state);
+    }
+}
+
+pub struct OnceLock<T> {
+    once: Once,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Send for OnceLock<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceLock<T> {}
+
+impl<T> OnceLock<T> {
+    pub const fn new() -> Self {
+        Self {
+            once: Once::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            // SAFETY: the value is written before the state becomes COMPLETE
+            // and never written again.
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.init(f, "Once")
+    }
+
+    fn init(&self, f: impl FnOnce() -> T, owner: &str) -> &T {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        self.once.call(
+            || {
+                // SAFETY: only the thread running `call_once` writes the value.
+                unsafe { (*self.value.get()).write(f()) };
+            },
+            owner,
+        );
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+}
+
+impl<T> Default for OnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for OnceLock<T> {
+    fn drop(&mut self) {
+        if *self.once.state.get_mut() == COMPLETE {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+pub struct LazyLock<T, F = fn() -> T> {
+    cell: OnceLock<T>,
+    init: UnsafeCell<Option<F>>,
+}
+
+unsafe impl<T: Send + Sync, F: Send> Sync for LazyLock<T, F> {}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    pub const fn new(f: F) -> Self {
+        Self {
+            cell: OnceLock::new(),
+            init: UnsafeCell::new(Some(f)),
+        }
+    }
+
+    /// Runs the initializer if it hasn't run yet and returns the value.
+    ///
+    /// If the initializer panics the `LazyLock` becomes poisoned and every
+    /// later access panics.
+    pub fn force(this: &Self) -> &T {
+        let init = || {
+            // SAFETY: only the thread running the initializer touches `init`,
+            // and it runs at most once: if it panics, the `Once` is poisoned
+            // and panics before getting here again. So `f` is still there.
+            let f = unsafe { (*this.init.get()).take().unwrap_unchecked() };
+            f()
+        };
+        this.cell.init(init, "LazyLock")
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        LazyLock::force(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic::AssertUnwindSafe, sync::atomic::AtomicUsize};
+
+    use super::*;
+
+    #[test]
+    fn test_call_once() {
+        let once = Once::new();
+        let calls = AtomicUsize::new(0);
+
+        once.call_once(|| {
+            calls.fetch_add(1, Ordering::Relaxed);
+        });
+        once.call_once(|| {
+            calls.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(once.is_completed());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_call_once_multi_thread() {
+        let once = Once::new();
+        let calls = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..10 {
+                s.spawn(|| {
+                    once.call_once(|| {
+                        std::thread::sleep(std::time::Duration::from_millis(50));
+                        calls.fetch_add(1, Ordering::Relaxed);
+                    });
+                    // Every caller must observe the completed initialization.
+                    assert_eq!(calls.load(Ordering::Relaxed), 1);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_poisoned() {
+        let once = Once::new();
+
+        let res = std::panic::catch_unwind(|| once.call_once(|| panic!("boom")));
+        assert!(res.is_err());
+        assert!(!once.is_completed());
+
+        let err = std::panic::catch_unwind(|| once.call_once(|| {})).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "Once instance has previously been poisoned"
+        );
+    }
+
+    #[test]
+    fn test_once_lock() {
+        let lock = OnceLock::new();
+        assert_eq!(lock.get(), None);
+
+        assert_eq!(lock.get_or_init(|| String::from("hello")), "hello");
+        assert_eq!(lock.get_or_init(|| String::from("world")), "hello");
+        assert_eq!(lock.get().map(String::as_str), Some("hello"));
+    }
+
+    #[test]
+    fn test_once_lock_multi_thread() {
+        static LOCK: OnceLock<usize> = OnceLock::new();
+        let calls = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for i in 0..10 {
+                let calls = &calls;
+                s.spawn(move || {
+                    let value = *LOCK.get_or_init(|| {
+                        calls.fetch_add(1, Ordering::Relaxed);
+                        i
+                    });
+                    assert_eq!(Some(&value), LOCK.get());
+                });
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_lazy_lock_static() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        static SQUARES: LazyLock<Vec<u64>> = LazyLock::new(|| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            (0..1000u64).map(|i| i * i).collect()
+        });
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| assert_eq!(SQUARES[999], 998001));
+            }
+        });
+
+        assert_eq!(SQUARES.len(), 1000);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_lazy_lock_closure() {
+        let base = 20;
+        let lazy = LazyLock::new(|| base + 22);
+        assert_eq!(*lazy, 42);
+        assert_eq!(*LazyLock::force(&lazy), 42);
+    }
+
+    #[test]
+    fn test_lazy_lock_poisoned() {
+        let lazy: LazyLock<u32, _> = LazyLock::new(|| panic!("boom"));
+
+        let err = std::panic::catch_unwind(AssertUnwindSafe(|| *lazy)).unwrap_err();
+        assert_eq!(err.downcast_ref::<&str>(), Some(&"boom"));
+
+        let err = std::panic::catch_unwind(AssertUnwindSafe(|| *lazy)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "LazyLock instance has previously been poisoned"
+        );
+    }
+}
@@ -1,36 +1,176 @@
 use std::{
     cell::UnsafeCell,
+    fmt::Display,
     mem::MaybeUninit,
-    sync::atomic::{AtomicBool, Ordering},
+    ops::Deref,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
     thread::Thread,
 };
 
+use crate::arc::Arc;
+
+// Channel states. `EMPTY` moves to exactly one of the other three; the
+// message slot is initialized only in `SENT`.
+const EMPTY: u8 = 0;
+const SENT: u8 = 1;
+const RECEIVER_DROPPED: u8 = 2;
+const SENDER_DROPPED: u8 = 3;
+// `EMPTY` with the receiver parked (or about to park) in `recv`: whoever
+// leaves this state must unpark it. Every other state has the bit clear.
+const PARKED: u8 = 0b100;
+
 pub struct Channel<T> {
     message: UnsafeCell<MaybeUninit<T>>,
-    ready: AtomicBool,
+    state: AtomicU8,
+    // Set by `split_static`, which only has `&self` to go on.
+    claimed: AtomicBool,
+}
+
+/// Returned by [`Receiver::recv`] when the `Sender` was dropped without
+/// sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("sender dropped without sending")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Returned by [`Channel::split`] when the channel has already been used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitError;
+
+impl Display for SplitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("one-shot channel already used; reset it first")
+    }
 }
 
+impl std::error::Error for SplitError {}
+
 impl<T> Channel<T> {
     pub const fn new() -> Self {
         Self {
             message: UnsafeCell::new(MaybeUninit::uninit()),
-            ready: AtomicBool::new(false),
+            state: AtomicU8::new(EMPTY),
+            claimed: AtomicBool::new(false),
         }
     }
 
-    pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
-        (
+    /// Hands out the two halves of an unused channel.
+    ///
+    /// Fails once the channel has been used (a message was sent or either
+    /// half was dropped); call [`Channel::reset`] to use it again.
+    pub fn split(&mut self) -> Result<(Sender<'_, T>, Receiver<'_, T>), SplitError> {
+        if *self.state.get_mut() != EMPTY {
+            return Err(SplitError);
+        }
+        Ok((
             Sender {
-                channel: self,
+                channel: Handle::Borrowed(self),
                 // Capture the current thread to ensure the sender can send messages
                 // This is necessary to ensure the sender can wake up the receiver
                 receiving_thread: std::thread::current(),
             },
             Receiver {
-                channel: self,
+                channel: Handle::Borrowed(self),
+                _send_marker: std::marker::PhantomData,
+            },
+        ))
+    }
+
+    /// Splits a channel in a `static`, which `split` can't reach because it
+    /// needs `&mut self`. Only the first call gets the halves; every later
+    /// one returns `None`, so a static channel is used exactly once.
+    ///
+    /// As with `split`, the calling thread is the one `send` wakes.
+    pub fn split_static(&'static self) -> Option<(Sender<'static, T>, Receiver<'static, T>)> {
+        if self.claimed.swap(true, Ordering::Relaxed) {
+            return None;
+        }
+        Some((
+            Sender {
+                channel: Handle::Borrowed(self),
+                receiving_thread: std::thread::current(),
+            },
+            Receiver {
+                channel: Handle::Borrowed(self),
                 _send_marker: std::marker::PhantomData,
             },
-        )
+        ))
+    }
+
+    /// Returns the channel to its unused state, dropping a message that was
+    /// sent but never received.
+    pub fn reset(&mut self) {
+        if *self.state.get_mut() == SENT {
+            unsafe { self.message.get_mut().assume_init_drop() };
+        }
+        *self.state.get_mut() = EMPTY;
+        *self.claimed.get_mut() = false;
+    }
+}
+
+/// Creates a one-shot channel whose halves own it jointly, so they can be
+/// moved anywhere, including into `std::thread::spawn`. The channel (and an
+/// unreceived message) is freed when both halves are gone.
+///
+/// As with [`Channel::split`], the calling thread is the one `send` wakes, so
+/// the `Receiver` stays on it.
+///
+/// This is the counterpart of [`safe::channel::channel`](crate::safe::channel::channel).
+/// The halves keep their lifetime parameter, since `split` shares the types,
+/// but here it is always `'static`.
+pub fn channel<T>() -> (Sender<'static, T>, Receiver<'static, T>) {
+    let channel = Arc::new(Channel::new());
+    (
+        Sender {
+            channel: Handle::Owned(channel.clone()),
+            receiving_thread: std::thread::current(),
+        },
+        Receiver {
+            channel: Handle::Owned(channel),
+            _send_marker: std::marker::PhantomData,
+        },
+    )
+}
+
+// How a half reaches the channel: borrowed from `split`, or shared with the
+// other half when created by `channel()`.
+enum Handle<'a, T> {
+    Borrowed(&'a Channel<T>),
+    Owned(Arc<Channel<T>>),
+}
+
+impl<T> Deref for Handle<'_, T> {
+    type Target = Channel<T>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Handle::Borrowed(channel) => channel,
+            Handle::Owned(channel) => channel,
+        }
+    }
+}
+
+impl<T> Channel<T> {
+    // Moves from `EMPTY` (parked or not) to `to`. On success, returns
+    // whether the receiver was parked; on failure, the state found instead.
+    fn leave_empty(&self, to: u8, success: Ordering) -> Result<bool, u8> {
+        let mut current = EMPTY;
+        loop {
+            match self
+                .state
+                .compare_exchange(current, to, success, Ordering::Relaxed)
+            {
+                Ok(_) => return Ok(current == PARKED),
+                Err(actual) if actual == EMPTY || actual == PARKED => current = actual,
+                Err(actual) => return Err(actual),
+            }
+        }
     }
 }
 
@@ -41,44 +181,202 @@ impl<T> Default for Channel<T> {
 }
 
 pub struct Sender<'a, T> {
-    channel: &'a Channel<T>,
+    channel: Handle<'a, T>,
     receiving_thread: Thread,
 }
 
 impl<'a, T> Sender<'a, T> {
-    pub fn send(self, message: T) {
+    /// Sends `message`, or hands it back if the `Receiver` is already gone.
+    pub fn send(self, message: T) -> Result<(), T> {
+        // `send` takes `self`, so this only fires if a second `Sender` was
+        // conjured up for the same channel, which would overwrite (and leak)
+        // the first message.
+        debug_assert!(
+            self.channel.state.load(Ordering::Relaxed) != SENT,
+            "one-shot channel already has a message"
+        );
+        self.send_inner(message)
+    }
+
+    /// Like [`Sender::send`], but checks in every build that the channel is
+    /// still empty, handing the message back instead of overwriting one.
+    pub fn send_checked(self, message: T) -> Result<(), T> {
+        if self.channel.state.load(Ordering::Acquire) == SENT {
+            return Err(message);
+        }
+        self.send_inner(message)
+    }
+
+    fn send_inner(&self, message: T) -> Result<(), T> {
+        if self.channel.state.load(Ordering::Relaxed) == RECEIVER_DROPPED {
+            return Err(message);
+        }
         unsafe {
             (*self.channel.message.get()).write(message);
         }
-        self.channel.ready.store(true, Ordering::Release);
-        Thread::unpark(&self.receiving_thread);
+        match self.channel.leave_empty(SENT, Ordering::Release) {
+            Ok(parked) => {
+                // A receiver that hasn't announced itself yet will see `SENT`
+                // before it parks, so the syscall is only needed here.
+                if parked {
+                    Thread::unpark(&self.receiving_thread);
+                }
+                Ok(())
+            }
+            // The receiver was dropped after the check above. The message was
+            // never published, so it is still ours to take back.
+            Err(_) => Err(unsafe { (*self.channel.message.get()).assume_init_read() }),
+        }
+    }
+}
+
+impl<T> Drop for Sender<'_, T> {
+    fn drop(&mut self) {
+        // Fails after a send (or once the receiver is gone), leaving the
+        // state alone.
+        if let Ok(true) = self.channel.leave_empty(SENDER_DROPPED, Ordering::Relaxed) {
+            Thread::unpark(&self.receiving_thread);
+        }
+    }
+}
+
+impl<T> Sender<'_, T> {
+    /// Whether the receiver is currently parked in [`Receiver::recv`], i.e.
+    /// whether sending now will cost a wakeup. A racy snapshot.
+    pub fn is_receiver_waiting(&self) -> bool {
+        self.channel.state.load(Ordering::Relaxed) == PARKED
     }
 }
 
 pub struct Receiver<'a, T> {
-    channel: &'a Channel<T>,
+    channel: Handle<'a, T>,
     // Use a marker to ensure the receiver does not send to other threads
     _send_marker: std::marker::PhantomData<*const ()>,
 }
 
-impl<T> Receiver<'_, T> {
-    pub fn recv(self) -> T {
-        while !self.channel.ready.swap(false, Ordering::Acquire) {
-            std::thread::park();
+impl<'a, T> Receiver<'a, T> {
+    /// Whether a message is waiting, i.e. whether `recv` would return
+    /// without blocking.
+    pub fn is_ready(&self) -> bool {
+        self.channel.state.load(Ordering::Acquire) == SENT
+    }
+
+    /// Takes the message if it has arrived, or hands the receiver back so
+    /// the caller can try again later.
+    pub fn try_recv(self) -> Result<T, Self> {
+        // Nothing is cleared unless the message is actually taken, so a
+        // failed attempt leaves the channel untouched.
+        if self.is_ready() {
+            Ok(self.take())
+        } else {
+            Err(self)
         }
-        unsafe {
-            // SAFETY: We assume the message is ready to be read
-            (*self.channel.message.get()).assume_init_read()
+    }
+
+    /// Blocks until the message arrives, or fails if the `Sender` was
+    /// dropped without sending.
+    pub fn recv(self) -> Result<T, RecvError> {
+        loop {
+            match self.channel.state.load(Ordering::Acquire) {
+                SENT => return Ok(self.take()),
+                SENDER_DROPPED => return Err(RecvError),
+                // Announce that we are going to sleep; if the sender got in
+                // first the exchange fails and we go round again.
+                EMPTY => {
+                    let _ = self.channel.state.compare_exchange(
+                        EMPTY,
+                        PARKED,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    );
+                }
+                // `park` may return spuriously or because someone else
+                // unparked us, so only the state decides when to stop.
+                _ => std::thread::park(),
+            }
         }
     }
+
+    /// Like [`Receiver::recv`], but first checks for the message up to
+    /// `spin` times before parking. Saves the park/unpark round trip when
+    /// the sender is expected to be quick.
+    pub fn recv_busy(self, spin: usize) -> Result<T, RecvError> {
+        for _ in 0..spin {
+            match self.channel.state.load(Ordering::Acquire) {
+                SENT => return Ok(self.take()),
+                SENDER_DROPPED => return Err(RecvError),
+                _ => std::hint::spin_loop(),
+            }
+        }
+        self.recv()
+    }
+
+    // Must only be called in the `SENT` state, after an Acquire load.
+    fn take(self) -> T {
+        // Back to `EMPTY` so the channel no longer owns the message; our own
+        // drop below then marks the receiver as gone.
+        self.channel.state.store(EMPTY, Ordering::Relaxed);
+        unsafe { (*self.channel.message.get()).assume_init_read() }
+    }
 }
 
+impl<T> Drop for Receiver<'_, T> {
+    fn drop(&mut self) {
+        // A message that was sent but never received stays in `SENT` and is
+        // dropped along with the channel.
+        let _ = self
+            .channel
+            .leave_empty(RECEIVER_DROPPED, Ordering::Relaxed);
+    }
+}
+
+// The message is only ever moved between threads, never shared by
+// reference, so `T: Send` is enough for both. The halves get their auto traits
+// from `Handle` and the `Receiver`'s marker: a `Sender` is `Send + Sync` when
+// `T: Send`, and a `Receiver` is neither, since `send` wakes the thread that
+// created it.
 unsafe impl<T: Send> Send for Channel<T> {}
 unsafe impl<T: Send> Sync for Channel<T> {}
 
+/// A channel of a `!Send` type cannot be shared.
+///
+/// ```compile_fail
+/// use learn_unsafe::one_shot::Channel;
+/// fn is_sync<T: Sync>() {}
+/// is_sync::<Channel<std::rc::Rc<i32>>>();
+/// ```
+///
+/// A `Sender` of a `!Send` type cannot leave its thread.
+///
+/// ```compile_fail
+/// use learn_unsafe::one_shot::channel;
+/// fn is_send<T: Send>(_: T) {}
+/// let (sender, _receiver) = channel::<std::rc::Rc<i32>>();
+/// is_send(sender);
+/// ```
+///
+/// A `Receiver` cannot leave its thread...
+///
+/// ```compile_fail
+/// use learn_unsafe::one_shot::channel;
+/// fn is_send<T: Send>(_: T) {}
+/// let (_sender, receiver) = channel::<i32>();
+/// is_send(receiver);
+/// ```
+///
+/// ...or be shared.
+///
+/// ```compile_fail
+/// use learn_unsafe::one_shot::channel;
+/// fn is_sync<T: Sync>(_: &T) {}
+/// let (_sender, receiver) = channel::<i32>();
+/// is_sync(&receiver);
+/// ```
+fn _auto_traits() {}
+
 impl<T> Drop for Channel<T> {
     fn drop(&mut self) {
-        if *self.ready.get_mut() {
+        if *self.state.get_mut() == SENT {
             // SAFETY: We are dropping the channel, so we can safely assume the message is ready
             unsafe {
                 (*self.message.get()).assume_init_drop();
@@ -96,12 +394,12 @@ mod tests {
     #[test]
     fn test_channel_single_thread() {
         let mut channel = Channel::new();
-        let (sender, receiver) = channel.split();
+        let (sender, receiver) = channel.split().unwrap();
 
-        sender.send(42);
+        sender.send(42).unwrap();
 
         let res = receiver.recv();
-        assert_eq!(res, 42);
+        assert_eq!(res, Ok(42));
     }
 
     #[test]
@@ -109,14 +407,322 @@ mod tests {
         use std::thread;
 
         let mut channel = Channel::new();
-        let (sender, receiver) = channel.split();
+        let (sender, receiver) = channel.split().unwrap();
 
         thread::scope(|s| {
             s.spawn(move || {
                 sleep(Duration::from_millis(100));
-                sender.send(42);
+                sender.send(42).unwrap();
             });
-            assert_eq!(receiver.recv(), 42);
+            assert_eq!(receiver.recv(), Ok(42));
+        });
+    }
+
+    #[test]
+    fn test_foreign_unpark_before_send() {
+        let (sender, receiver) = channel();
+        let receiving_thread = std::thread::current();
+        let (parked_tx, parked_rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            // Unrelated wakeups while the receiver is parked must not make
+            // `recv` read the still-uninitialized slot.
+            parked_rx.recv().unwrap();
+            for _ in 0..10 {
+                receiving_thread.unpark();
+                sleep(Duration::from_millis(5));
+            }
+            sender.send(vec![1, 2, 3]).unwrap();
+        });
+
+        parked_tx.send(()).unwrap();
+        assert_eq!(receiver.recv(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_unpark_only_when_parked() {
+        // Fast sender: the message is there before the receiver looks.
+        let (sender, receiver) = channel();
+        assert!(!sender.is_receiver_waiting());
+        sender.send(1).unwrap();
+        assert_eq!(receiver.recv(), Ok(1));
+
+        // Slow sender: the receiver has parked by the time it sends.
+        let (sender, receiver) = channel();
+        let handle = std::thread::spawn(move || {
+            while !sender.is_receiver_waiting() {
+                sleep(Duration::from_millis(1));
+            }
+            sender.send(2).unwrap();
+        });
+        assert_eq!(receiver.recv(), Ok(2));
+        handle.join().unwrap();
+
+        // And a dropped sender still wakes a parked receiver.
+        let (sender, receiver) = channel::<i32>();
+        let handle = std::thread::spawn(move || {
+            while !sender.is_receiver_waiting() {
+                sleep(Duration::from_millis(1));
+            }
+        });
+        assert_eq!(receiver.recv(), Err(RecvError));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_recv_busy() {
+        let (sender, receiver) = channel();
+        sender.send(1).unwrap();
+        assert_eq!(receiver.recv_busy(0), Ok(1));
+
+        // A fast sender is caught while spinning...
+        let (sender, receiver) = channel();
+        let handle = std::thread::spawn(move || sender.send(2).unwrap());
+        assert_eq!(receiver.recv_busy(1_000_000), Ok(2));
+        handle.join().unwrap();
+
+        // ...and a slow one once the spin gives up and parks.
+        let (sender, receiver) = channel();
+        let handle = std::thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            sender.send(3).unwrap();
+        });
+        assert_eq!(receiver.recv_busy(100), Ok(3));
+        handle.join().unwrap();
+    }
+
+    // Run with `cargo test --release -- --ignored bench_handoff --nocapture`.
+    //
+    // On a single-core VM spinning is a loss (~300µs vs ~21µs per handoff in
+    // a debug build): the sender can't run while the receiver spins. It can
+    // only pay off when the sender is on another core.
+    #[test]
+    #[ignore = "benchmark; run explicitly"]
+    fn bench_handoff() {
+        const ROUNDS: u32 = 10_000;
+
+        fn run(spin: usize) -> Duration {
+            let start = std::time::Instant::now();
+            for i in 0..ROUNDS {
+                let (sender, receiver) = channel();
+                let handle = std::thread::spawn(move || sender.send(i).unwrap());
+                assert_eq!(receiver.recv_busy(spin), Ok(i));
+                handle.join().unwrap();
+            }
+            start.elapsed() / ROUNDS
+        }
+
+        println!("park:         {:?} per handoff", run(0));
+        println!("spin, 10_000: {:?} per handoff", run(10_000));
+    }
+
+    #[test]
+    fn test_static_channel() {
+        static CHANNEL: Channel<String> = Channel::new();
+
+        let (sender, receiver) = CHANNEL.split_static().unwrap();
+        assert!(CHANNEL.split_static().is_none());
+        std::thread::spawn(move || sender.send(String::from("static")).unwrap());
+        assert_eq!(receiver.recv().as_deref(), Ok("static"));
+        assert!(CHANNEL.split_static().is_none());
+    }
+
+    #[test]
+    fn test_auto_traits() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_sync::<Channel<std::cell::Cell<i32>>>();
+        is_send::<Sender<'static, std::cell::Cell<i32>>>();
+        is_sync::<Sender<'static, std::cell::Cell<i32>>>();
+    }
+
+    #[test]
+    fn test_try_recv() {
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        assert!(!receiver.is_ready());
+        let receiver = receiver.try_recv().unwrap_err();
+
+        sender.send(7).unwrap();
+        assert!(receiver.is_ready());
+        assert_eq!(receiver.try_recv().ok(), Some(7));
+    }
+
+    #[test]
+    fn test_try_recv_polling() {
+        let (sender, mut receiver) = channel();
+        std::thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            sender.send("late").unwrap();
+        });
+
+        let mut attempts = 0;
+        let message = loop {
+            match receiver.try_recv() {
+                Ok(message) => break message,
+                Err(r) => receiver = r,
+            }
+            attempts += 1;
+            sleep(Duration::from_millis(1));
+        };
+        assert_eq!(message, "late");
+        assert!(attempts > 0);
+    }
+
+    // Stands in for a second `Sender` obtained by unsafe means.
+    fn forge_sender<'a, T>(receiver: &'a Receiver<'_, T>) -> Sender<'a, T> {
+        Sender {
+            channel: Handle::Borrowed(&receiver.channel),
+            receiving_thread: std::thread::current(),
+        }
+    }
+
+    #[test]
+    fn test_send_checked() {
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        assert_eq!(sender.send_checked(1), Ok(()));
+        assert_eq!(forge_sender(&receiver).send_checked(2), Err(2));
+        assert_eq!(receiver.recv(), Ok(1));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "one-shot channel already has a message")]
+    fn test_double_send_panics() {
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        sender.send(1).unwrap();
+        let _ = forge_sender(&receiver).send(2);
+    }
+
+    struct DropCounter<'a>(&'a std::sync::atomic::AtomicUsize);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_owned_channel_across_spawn() {
+        let (sender, receiver) = channel();
+        let handle = std::thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            sender.send(String::from("hello")).unwrap();
+        });
+        assert_eq!(receiver.recv().as_deref(), Ok("hello"));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_owned_channel_drops_message() {
+        static DROPS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        // Sent, but the receiver is dropped without receiving.
+        let (sender, receiver) = channel();
+        assert!(sender.send(DropCounter(&DROPS)).is_ok());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+        drop(receiver);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        // Never sent.
+        let (sender, receiver) = channel::<DropCounter>();
+        drop(sender);
+        drop(receiver);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        // Received: dropped by the caller, not again by the channel.
+        let (sender, receiver) = channel();
+        assert!(sender.send(DropCounter(&DROPS)).is_ok());
+        drop(receiver.recv());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_drop_orderings() {
+        let drops = std::sync::atomic::AtomicUsize::new(0);
+        let count = || drops.load(Ordering::Relaxed);
+
+        // Send, then receive.
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        assert!(sender.send(DropCounter(&drops)).is_ok());
+        assert!(receiver.recv().is_ok());
+        assert_eq!(count(), 1);
+        drop(channel);
+        assert_eq!(count(), 1);
+
+        // Send, then drop the receiver: the channel still owns the message.
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        assert!(sender.send(DropCounter(&drops)).is_ok());
+        drop(receiver);
+        assert_eq!(count(), 1);
+        drop(channel);
+        assert_eq!(count(), 2);
+
+        // Drop the receiver, then send: the message comes back.
+        let mut channel = Channel::new();
+        let (sender, receiver) = channel.split().unwrap();
+        drop(receiver);
+        let message = sender.send(DropCounter(&drops)).unwrap_err();
+        assert_eq!(count(), 2);
+        drop(message);
+        assert_eq!(count(), 3);
+        drop(channel);
+        assert_eq!(count(), 3);
+
+        // Drop the sender, then receive.
+        let mut channel = Channel::<DropCounter>::new();
+        let (sender, receiver) = channel.split().unwrap();
+        drop(sender);
+        assert!(matches!(receiver.recv(), Err(RecvError)));
+        drop(channel);
+        assert_eq!(count(), 3);
+    }
+
+    #[test]
+    fn test_reuse_after_reset() {
+        let mut channel = Channel::new();
+        for i in 0..3 {
+            let (sender, receiver) = channel.split().unwrap();
+            sender.send(i).unwrap();
+            assert_eq!(receiver.recv(), Ok(i));
+            assert!(matches!(channel.split(), Err(SplitError)));
+            channel.reset();
+        }
+    }
+
+    #[test]
+    fn test_reset_drops_unreceived_message() {
+        let drops = std::sync::atomic::AtomicUsize::new(0);
+        let mut channel = Channel::new();
+
+        let (sender, receiver) = channel.split().unwrap();
+        assert!(sender.send(DropCounter(&drops)).is_ok());
+        drop(receiver);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        channel.reset();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // Nothing is left for the channel's own drop.
+        let (sender, receiver) = channel.split().unwrap();
+        drop((sender, receiver));
+        channel.reset();
+        drop(channel);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_sender_dropped_wakes_receiver() {
+        let (sender, receiver) = channel::<i32>();
+        std::thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            drop(sender);
         });
+        assert_eq!(receiver.recv(), Err(RecvError));
+        assert_eq!(RecvError.to_string(), "sender dropped without sending");
     }
 }
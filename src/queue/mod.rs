@@ -0,0 +1,216 @@
+use std::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// A lock-free multi-producer single-consumer FIFO queue.
+///
+/// This is the Michael-Scott linked queue (a sentinel node, producers append
+/// at `tail`, the consumer advances `head`) in the MPSC form popularised by
+/// Dmitry Vyukov: a producer claims its place with a single `swap` on `tail`
+/// and then links the previous node to it. Unlike the CAS-on-`tail.next`
+/// formulation, a producer only ever dereferences the node it got back from
+/// that `swap`, and the consumer cannot free that node until the link is
+/// published, so no hazard pointers are needed.
+///
+/// Between the `swap` and the link, the pushed value is not yet visible and
+/// `pop` may briefly return `None` although a push is in flight.
+///
+/// Only `push` is lock-free. `pop` and `is_empty` take the sentinel out of
+/// `head` while they run, and a second consumer spins until it is put back;
+/// if the first one is descheduled meanwhile, the other burns its whole time
+/// slice. Callers with several consumers should serialize them with a lock
+/// that sleeps, as the executor's ready queue does.
+pub struct Queue<T> {
+    // The sentinel. Null while a consumer is inside `pop`.
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+}
+
+struct Node<T> {
+    next: AtomicPtr<Node<T>>,
+    value: Option<T>,
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Node<T> {
+    fn new(value: Option<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            value,
+        }))
+    }
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let sentinel = Node::new(None);
+        Self {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Node::new(Some(value));
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+        // SAFETY: `prev` cannot be freed before its `next` is set, because the
+        // consumer only frees a sentinel after moving past it.
+        unsafe { (*prev).next.store(node, Ordering::Release) };
+    }
+
+    /// Pops the oldest value.
+    ///
+    /// The queue is designed for one consumer; if several threads call `pop`
+    /// at once they are serialized by spinning on `head` (see the type docs).
+    pub fn pop(&self) -> Option<T> {
+        let head = self.claim_head();
+        let next = unsafe { (*head).next.load(Ordering::Acquire) };
+        if next.is_null() {
+            self.head.store(head, Ordering::Release);
+            return None;
+        }
+
+        // `next` becomes the new sentinel once its value is moved out.
+        let value = unsafe { (*next).value.take() };
+        self.head.store(next, Ordering::Release);
+        drop(unsafe { Box::from_raw(head) });
+        value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // Claimed like in `pop`: a concurrent `pop` could otherwise free the
+        // sentinel while we read it.
+        let head = self.claim_head();
+        let empty = unsafe { (*head).next.load(Ordering::Acquire).is_null() };
+        self.head.store(head, Ordering::Release);
+        empty
+    }
+
+    // Takes the sentinel out of `head`, spinning while another consumer
+    // holds it. The caller must store a sentinel back.
+    fn claim_head(&self) -> *mut Node<T> {
+        loop {
+            let head = self.head.swap(ptr::null_mut(), Ordering::Acquire);
+            if !head.is_null() {
+                break head;
+            }
+            std::hint::spin_loop();
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = *node.next.get_mut();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pop(), Some(1));
+
+        queue.push(3);
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_drop_remaining() {
+        let queue = Queue::new();
+        queue.push(String::from("a"));
+        queue.push(String::from("b"));
+        assert_eq!(queue.pop().as_deref(), Some("a"));
+        // "b" is dropped together with the queue.
+    }
+
+    #[test]
+    fn test_multi_producer() {
+        let queue = Queue::new();
+        let producers = 4;
+        let per_producer = 10_000;
+
+        std::thread::scope(|s| {
+            for p in 0..producers {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..per_producer {
+                        queue.push((p, i));
+                    }
+                });
+            }
+
+            let mut last = vec![None; producers];
+            let mut received = 0;
+            while received < producers * per_producer {
+                if let Some((p, i)) = queue.pop() {
+                    // Values from one producer arrive in the order they were pushed.
+                    assert!(last[p].is_none_or(|last| last < i));
+                    last[p] = Some(i);
+                    received += 1;
+                }
+            }
+        });
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_is_empty_during_pop() {
+        let queue = Queue::new();
+        let items = 10_000;
+
+        std::thread::scope(|s| {
+            let queue = &queue;
+            s.spawn(move || {
+                for i in 0..items {
+                    queue.push(Box::new(i));
+                }
+            });
+            s.spawn(move || {
+                let mut received = 0;
+                while received < items {
+                    if queue.pop().is_some() {
+                        received += 1;
+                    }
+                }
+            });
+            // Checks from other threads race with the consumer freeing
+            // sentinels.
+            for _ in 0..2 {
+                s.spawn(move || {
+                    for _ in 0..items {
+                        let _ = queue.is_empty();
+                    }
+                });
+            }
+        });
+
+        assert!(queue.is_empty());
+    }
+}
@@ -1,4 +1,11 @@
-use std::{cell::Cell, marker::PhantomData, ops::Deref, ptr::NonNull};
+use std::{
+    cell::Cell,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Deref,
+    ptr::NonNull,
+};
 
 pub struct Rc<T> {
     inner: NonNull<Inner<T>>,
@@ -43,6 +50,11 @@ impl<T> Rc<T> {
     pub fn strong(&self) -> usize {
         unsafe { self.inner.as_ref().strong.get() }
     }
+
+    /// Whether both `Rc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.inner == other.inner
+    }
 }
 
 impl<T> Deref for Rc<T> {
@@ -53,6 +65,46 @@ impl<T> Deref for Rc<T> {
     }
 }
 
+impl<T: Debug> Debug for Rc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for Rc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+// Comparisons and hashing use the pointed-to value; `ptr_eq` compares
+// identity.
+impl<T: PartialEq> PartialEq for Rc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Rc<T> {}
+
+impl<T: PartialOrd> PartialOrd for Rc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord> Ord for Rc<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: Hash> Hash for Rc<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
 impl<T> Drop for Rc<T> {
     fn drop(&mut self) {
         let inner = unsafe { self.inner.as_ref() };
@@ -90,4 +142,25 @@ mod tests {
         let rc = Rc::new(D);
         drop(rc);
     }
+
+    #[test]
+    fn display() {
+        let rc = Rc::new("shared");
+        assert_eq!(format!("{rc} {:>8}", rc.clone()), "shared   shared");
+    }
+
+    #[test]
+    fn value_comparisons() {
+        use std::collections::HashSet;
+
+        let a = Rc::new(vec![1, 2]);
+        let b = Rc::new(vec![1, 2]);
+        assert_eq!(a, b);
+        assert!(!Rc::ptr_eq(&a, &b));
+        assert!(Rc::ptr_eq(&a, &a.clone()));
+        assert!(Rc::new("a") < Rc::new("b"));
+
+        let set: HashSet<_> = [a, b, Rc::new(vec![3])].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
 }
@@ -0,0 +1,255 @@
+use std::mem::MaybeUninit;
+
+use crate::vec::Vec;
+
+/// A fixed-capacity double-ended queue stored in one contiguous allocation.
+///
+/// `head` is the slot of the front element and `tail` the slot one past the
+/// back element, both modulo the capacity. `len` tells a full buffer apart
+/// from an empty one, since `head == tail` in both cases.
+pub struct RingBuffer<T> {
+    buf: Vec<MaybeUninit<T>>,
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut buf = Vec::with_capacity(cap);
+        for _ in 0..cap {
+            buf.push(MaybeUninit::uninit());
+        }
+        Self {
+            buf,
+            head: 0,
+            tail: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    fn wrap_add(&self, index: usize, n: usize) -> usize {
+        (index + n) % self.capacity()
+    }
+
+    fn wrap_sub(&self, index: usize, n: usize) -> usize {
+        (index + self.capacity() - n) % self.capacity()
+    }
+
+    /// Appends `value`, or returns `false` without touching the buffer if it
+    /// is full.
+    pub fn push_back(&mut self, value: T) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.buf[self.tail].write(value);
+        self.tail = self.wrap_add(self.tail, 1);
+        self.len += 1;
+        true
+    }
+
+    /// Prepends `value`, or returns `false` if the buffer is full.
+    pub fn push_front(&mut self, value: T) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.head = self.wrap_sub(self.head, 1);
+        self.buf[self.head].write(value);
+        self.len += 1;
+        true
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: the `len` slots starting at `head` are initialized.
+        let value = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.wrap_add(self.head, 1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.tail = self.wrap_sub(self.tail, 1);
+        let value = unsafe { self.buf[self.tail].assume_init_read() };
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            let slot = self.wrap_add(self.head, index);
+            Some(unsafe { self.buf[slot].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> RingBufferIter<'_, T> {
+        RingBufferIter {
+            ring: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+pub struct RingBufferIter<'a, T> {
+    ring: &'a RingBuffer<T>,
+    // Logical indices into the ring, not slots.
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for RingBufferIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let item = self.ring.get(self.front);
+        self.front += 1;
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for RingBufferIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.ring.get(self.back)
+    }
+}
+
+impl<T> ExactSizeIterator for RingBufferIter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+
+    type IntoIter = RingBufferIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let mut ring = RingBuffer::with_capacity(3);
+        assert!(ring.is_empty());
+        assert!(ring.push_back(1));
+        assert!(ring.push_back(2));
+        assert!(ring.push_front(0));
+        assert!(ring.is_full());
+        assert!(!ring.push_back(3));
+        assert!(!ring.push_front(3));
+
+        assert_eq!(ring.pop_front(), Some(0));
+        assert_eq!(ring.pop_back(), Some(2));
+        assert_eq!(ring.pop_back(), Some(1));
+        assert_eq!(ring.pop_back(), None);
+        assert_eq!(ring.pop_front(), None);
+    }
+
+    #[test]
+    fn test_wrap_around() {
+        let mut ring = RingBuffer::with_capacity(3);
+        for i in 0..10 {
+            assert!(ring.push_back(i));
+            if ring.len() == 3 {
+                assert_eq!(ring.pop_front(), Some(i - 2));
+            }
+        }
+        assert_eq!(ring.iter().copied().collect::<std::vec::Vec<_>>(), [8, 9]);
+        assert_eq!(ring.front(), Some(&8));
+        assert_eq!(ring.back(), Some(&9));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut ring = RingBuffer::with_capacity(4);
+        ring.push_back(2);
+        ring.push_back(3);
+        ring.push_front(1);
+        ring.push_front(0);
+
+        let mut iter = ring.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!((&ring).into_iter().sum::<i32>(), 6);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut ring = RingBuffer::with_capacity(0);
+        assert!(ring.is_full());
+        assert!(!ring.push_back(1));
+        assert!(!ring.push_front(1));
+        assert_eq!(ring.pop_front(), None);
+        assert_eq!(ring.iter().next(), None);
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = std::rc::Rc::new(());
+        let mut ring = RingBuffer::with_capacity(3);
+        for _ in 0..3 {
+            ring.push_back(value.clone());
+        }
+        ring.pop_front();
+        ring.push_back(value.clone());
+        assert_eq!(std::rc::Rc::strong_count(&value), 4);
+        drop(ring);
+        assert_eq!(std::rc::Rc::strong_count(&value), 1);
+    }
+}
@@ -1,11 +1,17 @@
 use std::{
     cell::UnsafeCell,
+    fmt::{Debug, Display},
     ops::{Deref, DerefMut},
     sync::atomic::{AtomicU32, Ordering},
 };
 
 use atomic_wait::{wait, wake_all, wake_one};
 
+// Highest reader state a new reader may still start from. Keeping the count
+// below this leaves room for the "writer waiting" bit without ever reaching
+// `u32::MAX`, which is reserved for "write locked".
+const MAX_READ_STATE: u32 = u32::MAX - 3;
+
 pub struct RwLock<T> {
     state: AtomicU32,
     write_waker: AtomicU32,
@@ -22,6 +28,20 @@ pub struct WriteGuard<'a, T> {
 
 unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
 
+// Releasing the lock is a plain atomic store plus a futex wake, neither of
+// which cares which thread performs it, so (unlike a pthread-backed lock) a
+// guard may be dropped on a different thread than the one that acquired it.
+// What remains is the access a guard hands out:
+//
+// - a `ReadGuard` only yields `&T`, so sending or sharing it is sound exactly
+//   when `&T` may cross threads, i.e. `T: Sync`;
+// - a `WriteGuard` yields `&mut T`, so sending it needs `T: Send`, and sharing
+//   `&WriteGuard` only yields `&T`, which needs `T: Sync`.
+unsafe impl<T: Sync> Send for ReadGuard<'_, T> {}
+unsafe impl<T: Sync> Sync for ReadGuard<'_, T> {}
+unsafe impl<T: Send> Send for WriteGuard<'_, T> {}
+unsafe impl<T: Sync> Sync for WriteGuard<'_, T> {}
+
 impl<T> Deref for ReadGuard<'_, T> {
     type Target = T;
 
@@ -53,11 +73,19 @@ impl<T> RwLock<T> {
         }
     }
 
+    /// Acquires a shared read lock.
+    ///
+    /// Writers are preferred: once a writer has set the waiting bit, new
+    /// readers park instead of joining the readers already inside, so a
+    /// steady stream of readers cannot starve it.
+    ///
+    /// If the reader count is saturated (only reachable by leaking a huge
+    /// number of guards), this blocks until another reader releases instead
+    /// of panicking.
     pub fn read(&self) -> ReadGuard<'_, T> {
         let mut state = self.state.load(Ordering::Relaxed);
         loop {
-            if state.is_multiple_of(2) {
-                assert!(state < u32::MAX - 2, "too many readers");
+            if state.is_multiple_of(2) && state < MAX_READ_STATE {
                 match self.state.compare_exchange_weak(
                     state,
                     state + 2,
@@ -70,13 +98,31 @@ impl<T> RwLock<T> {
                     }
                 }
             }
-            if state % 2 == 1 {
+            // An odd state means a writer is queued behind the current
+            // readers; wait for it instead of slipping in ahead.
+            if state % 2 == 1 || state >= MAX_READ_STATE {
                 wait(&self.state, state);
                 state = self.state.load(Ordering::Relaxed);
             }
         }
     }
 
+    pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
+        let mut state = self.state.load(Ordering::Relaxed);
+        while state.is_multiple_of(2) && state < MAX_READ_STATE {
+            match self.state.compare_exchange_weak(
+                state,
+                state + 2,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(ReadGuard { lock: self }),
+                Err(new_state) => state = new_state,
+            }
+        }
+        None
+    }
+
     pub fn write(&self) -> WriteGuard<'_, T> {
         let mut state = self.state.load(Ordering::Relaxed);
         loop {
@@ -118,28 +164,105 @@ impl<T> RwLock<T> {
         }
     }
 
+    /// Runs `f` with a shared reference while holding a read lock.
+    ///
+    /// The lock is released when `f` returns or unwinds. Nesting `read_with`
+    /// is fine, but calling [`RwLock::write_with`] (or `write`) from inside
+    /// `f` deadlocks: the writer waits for this reader, which waits for `f`.
+    pub fn read_with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let lock = self.read();
+        f(&*lock)
+    }
+
+    /// Runs `f` with a mutable reference while holding the write lock.
+    ///
+    /// The lock is released when `f` returns or unwinds.
+    pub fn write_with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut lock = self.write();
+        f(&mut *lock)
+    }
+
+    /// The older name of [`read_with`](RwLock::read_with), kept as an alias.
     pub fn with_read<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&T) -> R,
     {
-        let lock = self.read();
-        f(&*lock)
+        self.read_with(f)
     }
 
+    /// The older name of [`write_with`](RwLock::write_with), kept as an
+    /// alias.
     pub fn with_write<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut T) -> R,
     {
-        let mut lock = self.write();
-        f(&mut *lock)
+        self.write_with(f)
+    }
+
+    /// Number of read guards currently held.
+    ///
+    /// This is a racy snapshot of the state word and may be stale by the
+    /// time it is returned; use it for diagnostics only.
+    pub fn reader_count(&self) -> usize {
+        match self.state.load(Ordering::Relaxed) {
+            u32::MAX => 0,
+            state => (state / 2) as usize,
+        }
+    }
+
+    /// Whether a write guard is currently held.
+    ///
+    /// Like [`RwLock::reader_count`], this is only a racy snapshot.
+    pub fn is_write_locked(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == u32::MAX
+    }
+}
+
+impl<T: Debug> Debug for RwLock<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked for writing>")),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug> Debug for ReadGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for ReadGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Debug> Debug for WriteGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for WriteGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
     }
 }
 
 impl<T> Drop for ReadGuard<'_, T> {
     fn drop(&mut self) {
-        if self.lock.state.fetch_sub(2, Ordering::Release) == 3 {
+        let state = self.lock.state.fetch_sub(2, Ordering::Release);
+        debug_assert!(state >= 2 && state != u32::MAX, "reader count underflow");
+        if state == 3 {
             self.lock.write_waker.fetch_add(1, Ordering::Release);
             wake_one(&self.lock.write_waker);
+        } else if state >= MAX_READ_STATE {
+            // A reader may be parked on a saturated count.
+            wake_one(&self.lock.state);
         }
     }
 }
@@ -153,9 +276,50 @@ impl<T> Drop for WriteGuard<'_, T> {
     }
 }
 
+/// A `ReadGuard` of a `!Sync` type cannot leave its thread.
+///
+/// ```compile_fail
+/// use learn_unsafe::rw_lock::RwLock;
+/// fn is_send<T: Send>(_: T) {}
+/// let rw = RwLock::new(std::cell::Cell::new(1));
+/// is_send(rw.read());
+/// ```
+///
+/// A `WriteGuard` of a `!Send` type cannot leave its thread.
+///
+/// ```compile_fail
+/// use learn_unsafe::rw_lock::RwLock;
+/// fn is_send<T: Send>(_: T) {}
+/// let rw = RwLock::new(std::rc::Rc::new(1));
+/// is_send(rw.write());
+/// ```
+///
+/// A `WriteGuard` of a `!Sync` type cannot be shared.
+///
+/// ```compile_fail
+/// use learn_unsafe::rw_lock::RwLock;
+/// fn is_sync<T: Sync>(_: T) {}
+/// let rw = RwLock::new(std::cell::Cell::new(1));
+/// is_sync(rw.write());
+/// ```
+fn _guard_auto_traits() {}
+
+#[cfg(test)]
+impl<T> RwLock<T> {
+    fn force_state(&self, state: u32) {
+        self.state.store(state, Ordering::Relaxed);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::RwLock;
+    use std::{
+        panic::AssertUnwindSafe,
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use super::{MAX_READ_STATE, ReadGuard, RwLock, WriteGuard};
 
     #[test]
     fn test_single_thread() {
@@ -213,4 +377,313 @@ mod tests {
         let sum = rw.with_read(|data| data.iter().sum::<i32>());
         assert_eq!(sum, 10);
     }
+
+    #[test]
+    fn test_read_with() {
+        let rw = RwLock::new(vec![1, 2, 3]);
+        let sum = rw.read_with(|data| data.iter().sum::<i32>());
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn test_write_with() {
+        let rw = RwLock::new(vec![1, 2, 3]);
+        rw.write_with(|data| data.push(4));
+        let sum = rw.read_with(|data| data.iter().sum::<i32>());
+        assert_eq!(sum, 10);
+    }
+
+    #[test]
+    fn test_read_with_nested() {
+        let rw = RwLock::new(5);
+        let product = rw.read_with(|a| rw.read_with(|b| a * b));
+        assert_eq!(product, 25);
+        assert_eq!(rw.reader_count(), 0);
+    }
+
+    #[test]
+    fn test_with_panic_releases() {
+        let rw = RwLock::new(0);
+
+        let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            rw.write_with(|data| {
+                *data = 1;
+                panic!("boom");
+            })
+        }));
+        assert!(res.is_err());
+        assert!(!rw.is_write_locked());
+
+        let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            rw.read_with(|_| panic!("boom"));
+        }));
+        assert!(res.is_err());
+        assert_eq!(rw.reader_count(), 0);
+
+        assert_eq!(rw.write_with(|data| std::mem::replace(data, 2)), 1);
+        assert_eq!(rw.read_with(|data| *data), 2);
+    }
+
+    #[test]
+    #[ignore = "demonstrates that write_with inside read_with deadlocks"]
+    fn test_write_with_inside_read_with_deadlocks() {
+        let rw = RwLock::new(0);
+        rw.read_with(|_| rw.write_with(|data| *data += 1));
+    }
+
+    #[test]
+    fn test_reader_overflow_blocks() {
+        let rw = RwLock::new(0);
+        rw.force_state(MAX_READ_STATE - 2);
+
+        let r = rw.read();
+        assert_eq!(rw.state.load(Ordering::Relaxed), MAX_READ_STATE);
+
+        let acquired = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let r = rw.read();
+                acquired.store(true, Ordering::Release);
+                assert_eq!(*r, 0);
+            });
+
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            assert!(!acquired.load(Ordering::Acquire));
+            drop(r);
+        });
+
+        assert!(acquired.load(Ordering::Acquire));
+        assert_eq!(rw.state.load(Ordering::Relaxed), MAX_READ_STATE - 2);
+    }
+
+    #[test]
+    fn test_writer_not_starved_by_readers() {
+        let rw = RwLock::new(0);
+        let done = AtomicBool::new(false);
+        // Lets the readers stop on their own if the writer never gets in.
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        let r = rw.read();
+        std::thread::scope(|s| {
+            let writer = s.spawn(|| {
+                let start = Instant::now();
+                *rw.write() += 1;
+                done.store(true, Ordering::Relaxed);
+                start.elapsed()
+            });
+            while rw.state.load(Ordering::Relaxed).is_multiple_of(2) {
+                std::thread::yield_now();
+            }
+
+            // A stream of overlapping readers; without writer preference
+            // the reader count would never drop to zero while they run.
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while !done.load(Ordering::Relaxed) && Instant::now() < deadline {
+                        let r = rw.read();
+                        std::hint::black_box(*r);
+                    }
+                });
+            }
+            std::thread::sleep(Duration::from_millis(50));
+            drop(r);
+
+            assert!(writer.join().unwrap() < Duration::from_secs(2));
+        });
+        assert_eq!(*rw.read(), 1);
+    }
+
+    #[test]
+    fn test_debug_uncontended() {
+        let rw = RwLock::new(vec![1, 2]);
+        assert_eq!(format!("{rw:?}"), "RwLock { data: [1, 2], .. }");
+
+        let r = rw.read();
+        assert_eq!(format!("{r:?}"), "[1, 2]");
+        assert_eq!(format!("{rw:?}"), "RwLock { data: [1, 2], .. }");
+    }
+
+    #[test]
+    fn test_debug_write_locked() {
+        let rw = &RwLock::new(42);
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                let w = rw.write();
+                assert_eq!(format!("{w} {w:?}"), "42 42");
+                locked_tx.send(()).unwrap();
+                done_rx.recv().unwrap();
+            });
+
+            locked_rx.recv().unwrap();
+            assert!(rw.is_write_locked());
+            assert_eq!(rw.reader_count(), 0);
+            assert_eq!(
+                format!("{rw:?}"),
+                "RwLock { data: <locked for writing>, .. }"
+            );
+            done_tx.send(()).unwrap();
+        });
+
+        assert!(!rw.is_write_locked());
+    }
+
+    #[test]
+    fn test_reader_count() {
+        let rw = RwLock::new(());
+        assert_eq!(rw.reader_count(), 0);
+
+        let r1 = rw.read();
+        let r2 = rw.read();
+        assert_eq!(rw.reader_count(), 2);
+        assert!(!rw.is_write_locked());
+
+        drop(r1);
+        assert_eq!(rw.reader_count(), 1);
+        drop(r2);
+        assert_eq!(rw.reader_count(), 0);
+    }
+
+    #[test]
+    fn test_guard_send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<ReadGuard<'_, i32>>();
+        is_sync::<ReadGuard<'_, i32>>();
+        is_send::<WriteGuard<'_, i32>>();
+        is_sync::<WriteGuard<'_, i32>>();
+
+        // `MutexGuard` is Sync but not Send.
+        is_send::<ReadGuard<'_, std::sync::MutexGuard<'_, i32>>>();
+        is_sync::<WriteGuard<'_, std::sync::MutexGuard<'_, i32>>>();
+
+        // `Cell` is Send but not Sync.
+        is_send::<WriteGuard<'_, std::cell::Cell<i32>>>();
+    }
+
+    #[test]
+    fn test_guard_dropped_on_other_thread() {
+        let rw = RwLock::new(vec![1]);
+
+        let r = rw.read();
+        std::thread::scope(|s| {
+            s.spawn(move || assert_eq!(r.len(), 1));
+        });
+        assert_eq!(rw.reader_count(), 0);
+
+        let mut w = rw.write();
+        w.push(2);
+        std::thread::scope(|s| {
+            s.spawn(move || w.push(3));
+        });
+        assert!(!rw.is_write_locked());
+        assert_eq!(*rw.read(), [1, 2, 3]);
+    }
+
+    // Run with `cargo test --release -- --ignored bench_rwlock --nocapture`.
+    //
+    // On a single-core VM (release build, 8 threads timesliced) std's RwLock
+    // and our Mutex both did ~40-50M ops/s in every workload. Our RwLock did
+    // ~22M ops/s uncontended and read-heavy, and only ~4M ops/s at 50/50,
+    // because every write unlock issues two futex wake syscalls whether or not
+    // anyone is waiting. There was no read-heavy crossover in its favour on
+    // that machine; re-run on multi-core hardware before drawing conclusions.
+    mod bench {
+        use std::time::Instant;
+
+        use crate::{mutex::Mutex, rw_lock::RwLock};
+
+        const THREADS: usize = 8;
+        const ITERATIONS: usize = 100_000;
+
+        trait Lock: Sync {
+            fn read_op(&self) -> u64;
+            fn write_op(&self);
+            fn value(&self) -> u64;
+        }
+
+        impl Lock for RwLock<u64> {
+            fn read_op(&self) -> u64 {
+                *self.read()
+            }
+            fn write_op(&self) {
+                *self.write() += 1;
+            }
+            fn value(&self) -> u64 {
+                *self.read()
+            }
+        }
+
+        impl Lock for std::sync::RwLock<u64> {
+            fn read_op(&self) -> u64 {
+                *self.read().unwrap()
+            }
+            fn write_op(&self) {
+                *self.write().unwrap() += 1;
+            }
+            fn value(&self) -> u64 {
+                *self.read().unwrap()
+            }
+        }
+
+        impl Lock for Mutex<u64> {
+            fn read_op(&self) -> u64 {
+                *self.lock()
+            }
+            fn write_op(&self) {
+                *self.lock() += 1;
+            }
+            fn value(&self) -> u64 {
+                *self.lock()
+            }
+        }
+
+        // Returns operations per second.
+        fn run(lock: &dyn Lock, threads: usize, write_percent: usize) -> f64 {
+            let start = Instant::now();
+            std::thread::scope(|s| {
+                for _ in 0..threads {
+                    s.spawn(|| {
+                        for i in 0..ITERATIONS {
+                            if i % 100 < write_percent {
+                                lock.write_op();
+                            } else {
+                                std::hint::black_box(lock.read_op());
+                            }
+                        }
+                    });
+                }
+            });
+            let elapsed = start.elapsed();
+
+            let writes_per_thread = (0..ITERATIONS).filter(|i| i % 100 < write_percent).count();
+            assert_eq!(lock.value(), (threads * writes_per_thread) as u64);
+
+            (threads * ITERATIONS) as f64 / elapsed.as_secs_f64()
+        }
+
+        #[test]
+        #[ignore = "benchmark; run explicitly"]
+        fn bench_rwlock() {
+            let workloads = [
+                ("read-heavy 95/5", THREADS, 5),
+                ("write-heavy 50/50", THREADS, 50),
+                ("uncontended", 1, 5),
+            ];
+
+            for (name, threads, write_percent) in workloads {
+                let ours = run(&RwLock::new(0), threads, write_percent);
+                let std = run(&std::sync::RwLock::new(0), threads, write_percent);
+                let mutex = run(&Mutex::new(0), threads, write_percent);
+
+                println!("{name} ({threads} threads):");
+                println!("  RwLock:      {ours:>14.0} ops/s");
+                println!("  std RwLock:  {std:>14.0} ops/s");
+                println!("  Mutex:       {mutex:>14.0} ops/s");
+            }
+        }
+    }
 }
@@ -3,3 +3,5 @@ pub mod future;
 pub mod iter;
 pub mod lifetime;
 pub mod sort;
+
+pub use iter::Delimiter;
@@ -1,5 +1,6 @@
 use std::{
     collections::VecDeque,
+    iter::FusedIterator,
     marker::PhantomData,
     sync::{Arc, Condvar, Mutex},
 };
@@ -18,6 +19,12 @@ impl<T> Sender<T> {
         drop(shared);
         self.shared.available.notify_one();
     }
+
+    /// Whether the receiver has been dropped, so nothing sent will ever be
+    /// received.
+    pub fn is_disconnected(&self) -> bool {
+        !self.shared.inner.lock().unwrap().receiver_alive
+    }
 }
 
 impl<T> Clone for Sender<T> {
@@ -71,6 +78,18 @@ impl<T> Receiver<T> {
             }
         }
     }
+
+    /// Whether every sender has been dropped. Messages sent before that may
+    /// still be waiting; `recv` returns them before it returns `None`.
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.inner.lock().unwrap().senders == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.inner.lock().unwrap().receiver_alive = false;
+    }
 }
 
 impl<T> Iterator for Receiver<T> {
@@ -81,6 +100,10 @@ impl<T> Iterator for Receiver<T> {
     }
 }
 
+// Once the last sender is gone none can be created again, so `recv` keeps
+// returning `None`.
+impl<T> FusedIterator for Receiver<T> {}
+
 struct Shared<T> {
     inner: Mutex<Inner<T>>,
     available: Condvar,
@@ -89,6 +112,7 @@ struct Shared<T> {
 struct Inner<T> {
     queue: VecDeque<T>,
     senders: usize,
+    receiver_alive: bool,
 }
 
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
@@ -96,6 +120,7 @@ pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
         inner: Mutex::new(Inner {
             queue: VecDeque::new(),
             senders: 1,
+            receiver_alive: true,
         }),
         available: Condvar::new(),
     };
@@ -124,6 +149,76 @@ mod tests {
         assert_eq!(rx.recv(), Some(42));
     }
 
+    #[test]
+    fn for_loop() {
+        let (tx, rx) = channel();
+        let producer = std::thread::spawn(move || {
+            for i in 0..5 {
+                tx.send(i);
+            }
+        });
+        let mut received = Vec::new();
+        for msg in rx {
+            received.push(msg);
+        }
+        producer.join().unwrap();
+        assert_eq!(received, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn fused() {
+        fn is_fused<I: std::iter::FusedIterator>(_: &I) {}
+
+        let (tx, mut rx) = channel();
+        is_fused(&rx);
+        tx.send(1);
+        drop(tx);
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), None);
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn disconnected() {
+        let (tx, mut rx) = channel();
+        let tx2 = tx.clone();
+        assert!(!tx.is_disconnected());
+        assert!(!rx.is_disconnected());
+
+        tx.send(1);
+        drop(tx);
+        assert!(!rx.is_disconnected());
+        drop(tx2);
+        assert!(rx.is_disconnected());
+        // Still delivers what was sent before the disconnect.
+        assert_eq!(rx.recv(), Some(1));
+        assert_eq!(rx.recv(), None);
+
+        let (tx, rx) = channel::<i32>();
+        drop(rx);
+        assert!(tx.is_disconnected());
+    }
+
+    #[test]
+    fn worker_exits_on_disconnect() {
+        let (tx, mut rx) = channel();
+        let worker = std::thread::spawn(move || {
+            let mut sum = 0;
+            while !rx.is_disconnected() {
+                if let Some(n) = rx.recv() {
+                    sum += n;
+                }
+            }
+            // Drain whatever arrived before the disconnect.
+            sum + rx.sum::<i32>()
+        });
+        for n in 1..=4 {
+            tx.send(n);
+        }
+        drop(tx);
+        assert_eq!(worker.join().unwrap(), 10);
+    }
+
     #[test]
     fn closed() {
         let (tx, mut rx) = channel::<i32>();
@@ -1,16 +1,71 @@
+use std::iter::{FusedIterator, Product, Sum};
+
+use crate::vec::Vec;
+
 pub struct StrSplit<'haystack, D> {
     remainder: Option<&'haystack str>,
     delimiter: D,
 }
 
-trait Delimiter {
+impl<'haystack, D> StrSplit<'haystack, D> {
+    pub fn new(haystack: &'haystack str, delimiter: D) -> Self {
+        Self {
+            remainder: Some(haystack),
+            delimiter,
+        }
+    }
+}
+
+/// Something [`StrSplit`] can split on.
+///
+/// Any `Fn(&str) -> Option<(usize, usize)>` closure is a delimiter too, so
+/// one-off delimiters don't need a type of their own.
+pub trait Delimiter {
+    /// Returns the byte range `(start, end)` of the first match in
+    /// `haystack`. Both must lie on `char` boundaries.
     fn find_next(&self, haystack: &str) -> Option<(usize, usize)>;
+
+    /// Like `find_next`, but finds the last match of a forward scan: the
+    /// last of the matches `find_next` finds when called repeatedly from the
+    /// start.
+    ///
+    /// The default walks every match with `find_next`, so it is linear in
+    /// the haystack. Override it only when a reverse search gives the same
+    /// answer; for patterns that can overlap (`"aa"` in `"aaa"`) it doesn't.
+    fn find_prev(&self, haystack: &str) -> Option<(usize, usize)> {
+        let mut last = None;
+        let mut offset = 0;
+        while let Some((start, end)) = self.find_next(&haystack[offset..]) {
+            last = Some((offset + start, offset + end));
+            offset += end;
+            if start == end {
+                // Step over a char so an empty match can't loop forever.
+                match haystack[offset..].chars().next() {
+                    Some(c) => offset += c.len_utf8(),
+                    None => break,
+                }
+            }
+        }
+        last
+    }
+}
+
+impl<F> Delimiter for F
+where
+    F: Fn(&str) -> Option<(usize, usize)>,
+{
+    fn find_next(&self, haystack: &str) -> Option<(usize, usize)> {
+        self(haystack)
+    }
 }
 
 impl Delimiter for &str {
     fn find_next(&self, haystack: &str) -> Option<(usize, usize)> {
         haystack.find(self).map(|index| (index, index + self.len()))
     }
+
+    // No `rfind` override: a string pattern can overlap itself, and then the
+    // last match from the back is not the last one from the front.
 }
 
 impl Delimiter for char {
@@ -19,6 +74,14 @@ impl Delimiter for char {
             .find(*self)
             .map(|index| (index, index + self.len_utf8()))
     }
+
+    // A `char` can't overlap itself, so searching from the back finds the
+    // same match as the forward scan.
+    fn find_prev(&self, haystack: &str) -> Option<(usize, usize)> {
+        haystack
+            .rfind(*self)
+            .map(|index| (index, index + self.len_utf8()))
+    }
 }
 
 impl<'haystack, D: Delimiter> Iterator for StrSplit<'haystack, D> {
@@ -37,6 +100,56 @@ impl<'haystack, D: Delimiter> Iterator for StrSplit<'haystack, D> {
     }
 }
 
+// Both ends cut from the same `remainder`, and `find_prev` agrees with the
+// forward scan, so mixing `next` and `next_back` yields the same pieces as
+// going in one direction.
+impl<'haystack, D: Delimiter> DoubleEndedIterator for StrSplit<'haystack, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let remainder = self.remainder?;
+        let Some((start, end)) = self.delimiter.find_prev(remainder) else {
+            return self.remainder.take();
+        };
+        self.remainder = Some(&remainder[..start]);
+        Some(&remainder[end..])
+    }
+}
+
+/// Splits `s` on `delimiter` into at most `n` pieces; the last piece is
+/// whatever is left, delimiters and all. `n == 0` yields nothing.
+pub fn splitn<'h, D: Delimiter>(s: &'h str, n: usize, delimiter: D) -> SplitN<'h, D> {
+    SplitN {
+        split: StrSplit {
+            remainder: Some(s),
+            delimiter,
+        },
+        remaining: n,
+    }
+}
+
+pub struct SplitN<'h, D> {
+    split: StrSplit<'h, D>,
+    // Pieces still allowed, counting the final unsplit one.
+    remaining: usize,
+}
+
+impl<'h, D: Delimiter> Iterator for SplitN<'h, D> {
+    type Item = &'h str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.remaining {
+            0 => None,
+            1 => {
+                self.remaining = 0;
+                self.split.remainder.take()
+            }
+            _ => {
+                self.remaining -= 1;
+                self.split.next()
+            }
+        }
+    }
+}
+
 pub trait IteratorExt: Iterator {
     fn my_flatten(self) -> Flatten<Self>
     where
@@ -52,6 +165,130 @@ pub trait IteratorExt: Iterator {
     {
         Map::new(self, f)
     }
+
+    /// Splits an iterator of pairs into a vector of firsts and a vector of
+    /// seconds.
+    fn my_unzip<A, B>(self) -> (Vec<A>, Vec<B>)
+    where
+        Self: Sized + Iterator<Item = (A, B)>,
+    {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for (a, b) in self {
+            left.push(a);
+            right.push(b);
+        }
+        (left, right)
+    }
+
+    /// Sorts items into those matching `p` and those that don't, keeping
+    /// their order.
+    fn my_partition<P>(self, mut p: P) -> (Vec<Self::Item>, Vec<Self::Item>)
+    where
+        Self: Sized,
+        P: FnMut(&Self::Item) -> bool,
+    {
+        let mut matched = Vec::new();
+        let mut rest = Vec::new();
+        for item in self {
+            if p(&item) {
+                matched.push(item);
+            } else {
+                rest.push(item);
+            }
+        }
+        (matched, rest)
+    }
+
+    /// The smallest item; the first of several equal ones.
+    fn my_min(self) -> Option<Self::Item>
+    where
+        Self: Sized,
+        Self::Item: Ord,
+    {
+        self.reduce(|min, item| if item < min { item } else { min })
+    }
+
+    /// The largest item; the last of several equal ones.
+    fn my_max(self) -> Option<Self::Item>
+    where
+        Self: Sized,
+        Self::Item: Ord,
+    {
+        self.reduce(|max, item| if item >= max { item } else { max })
+    }
+
+    fn my_sum<S>(self) -> S
+    where
+        Self: Sized,
+        S: Sum<Self::Item>,
+    {
+        S::sum(self)
+    }
+
+    fn my_product<P>(self) -> P
+    where
+        Self: Sized,
+        P: Product<Self::Item>,
+    {
+        P::product(self)
+    }
+
+    fn my_cloned<'a, T>(self) -> Cloned<Self>
+    where
+        Self: Sized + Iterator<Item = &'a T>,
+        T: Clone + 'a,
+    {
+        Cloned::new(self)
+    }
+
+    fn my_copied<'a, T>(self) -> Copied<Self>
+    where
+        Self: Sized + Iterator<Item = &'a T>,
+        T: Copy + 'a,
+    {
+        Copied::new(self)
+    }
+
+    /// Repeats the iterator forever by starting over from a clone of the
+    /// original each time it runs out. An empty iterator stays empty.
+    fn my_cycle(self) -> Cycle<Self>
+    where
+        Self: Sized + Clone,
+    {
+        Cycle::new(self)
+    }
+
+    fn my_rev(self) -> Rev<Self>
+    where
+        Self: Sized + DoubleEndedIterator,
+    {
+        Rev::new(self)
+    }
+
+    /// Groups items into owned [`Vec`](crate::vec::Vec)s of `size`; the last
+    /// one may be shorter.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    fn chunks(self, size: usize) -> Chunks<Self>
+    where
+        Self: Sized,
+    {
+        Chunks::new(self, size)
+    }
+
+    /// Like `take_while`, but also yields the first item that fails
+    /// `predicate` before stopping, e.g. to keep the terminator of a
+    /// message.
+    fn take_while_inclusive<P>(self, predicate: P) -> TakeWhileInclusive<Self, P>
+    where
+        Self: Sized,
+        P: FnMut(&Self::Item) -> bool,
+    {
+        TakeWhileInclusive::new(self, predicate)
+    }
 }
 
 impl<T> IteratorExt for T where T: Iterator {}
@@ -177,6 +414,238 @@ where
     }
 }
 
+pub struct Cloned<I> {
+    iter: I,
+}
+
+impl<I> Cloned<I> {
+    pub fn new(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<'a, I, T> Iterator for Cloned<I>
+where
+    I: Iterator<Item = &'a T>,
+    T: Clone + 'a,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().cloned()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, I, T> DoubleEndedIterator for Cloned<I>
+where
+    I: DoubleEndedIterator<Item = &'a T>,
+    T: Clone + 'a,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().cloned()
+    }
+}
+
+pub struct Copied<I> {
+    iter: I,
+}
+
+impl<I> Copied<I> {
+    pub fn new(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<'a, I, T> Iterator for Copied<I>
+where
+    I: Iterator<Item = &'a T>,
+    T: Copy + 'a,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, I, T> DoubleEndedIterator for Copied<I>
+where
+    I: DoubleEndedIterator<Item = &'a T>,
+    T: Copy + 'a,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().copied()
+    }
+}
+
+pub struct Cycle<I> {
+    orig: I,
+    iter: I,
+}
+
+impl<I: Clone> Cycle<I> {
+    pub fn new(iter: I) -> Self {
+        Self {
+            orig: iter.clone(),
+            iter,
+        }
+    }
+}
+
+impl<I> Iterator for Cycle<I>
+where
+    I: Iterator + Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            None => {
+                self.iter = self.orig.clone();
+                // Still `None` here means the original is empty.
+                self.iter.next()
+            }
+            item => item,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.orig.size_hint() {
+            (0, Some(0)) => (0, Some(0)),
+            (0, _) => (0, None),
+            _ => (usize::MAX, None),
+        }
+    }
+}
+
+pub struct Rev<I> {
+    iter: I,
+}
+
+impl<I> Rev<I> {
+    pub fn new(iter: I) -> Self {
+        Self { iter }
+    }
+
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: DoubleEndedIterator> Iterator for Rev<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Rev<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+pub struct Chunks<I> {
+    iter: I,
+    size: usize,
+}
+
+impl<I> Chunks<I> {
+    pub fn new(iter: I, size: usize) -> Self {
+        assert!(size != 0, "chunk size must be non-zero");
+        Self { iter, size }
+    }
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.iter.next()?;
+        let mut chunk = Vec::with_capacity(self.size);
+        chunk.push(first);
+        chunk.extend(self.iter.by_ref().take(self.size - 1));
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (
+            lower.div_ceil(self.size),
+            upper.map(|upper| upper.div_ceil(self.size)),
+        )
+    }
+}
+
+pub struct TakeWhileInclusive<I, P> {
+    iter: I,
+    predicate: P,
+    // Set once the failing item has been yielded (or `iter` ran out).
+    done: bool,
+}
+
+impl<I, P> TakeWhileInclusive<I, P> {
+    pub fn new(iter: I, predicate: P) -> Self {
+        Self {
+            iter,
+            predicate,
+            done: false,
+        }
+    }
+}
+
+impl<I, P> Iterator for TakeWhileInclusive<I, P>
+where
+    I: Iterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let Some(item) = self.iter.next() else {
+            self.done = true;
+            return None;
+        };
+        if !(self.predicate)(&item) {
+            self.done = true;
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            // The first item may already fail, but it is still yielded.
+            let (lower, upper) = self.iter.size_hint();
+            (lower.min(1), upper)
+        }
+    }
+}
+
+impl<I, P> FusedIterator for TakeWhileInclusive<I, P>
+where
+    I: Iterator,
+    P: FnMut(&I::Item) -> bool,
+{
+}
+
 #[cfg(test)]
 mod tests {
     use crate::safe::iter::IteratorExt;
@@ -252,6 +721,114 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    pub fn split_double_ended() {
+        let s = "a,b,,c🌍d,e";
+        let split = |delimiter| super::StrSplit {
+            remainder: Some(s),
+            delimiter,
+        };
+        let forward: Vec<_> = split(',').collect();
+        let mut backward: Vec<_> = split(',').rev().collect();
+        backward.reverse();
+        assert_eq!(forward, ["a", "b", "", "c🌍d", "e"]);
+        assert_eq!(backward, forward);
+
+        let mut iter = split(',');
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        while let Some(piece) = iter.next() {
+            front.push(piece);
+            match iter.next_back() {
+                Some(piece) => back.push(piece),
+                None => break,
+            }
+        }
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, forward);
+
+        let mut iter = super::StrSplit {
+            remainder: Some(s),
+            delimiter: "🌍",
+        };
+        assert_eq!(iter.next_back(), Some("d,e"));
+        assert_eq!(iter.next(), Some("a,b,,c"));
+        assert_eq!(iter.next_back(), None);
+
+        // An overlapping pattern splits the same way from either end.
+        for s in ["aaa", "aaaa", "baaab"] {
+            let split = || super::StrSplit {
+                remainder: Some(s),
+                delimiter: "aa",
+            };
+            let forward: Vec<_> = split().collect();
+            let mut backward: Vec<_> = split().rev().collect();
+            backward.reverse();
+            assert_eq!(backward, forward, "splitting {s:?}");
+        }
+        let overlapping = super::StrSplit {
+            remainder: Some("aaa"),
+            delimiter: "aa",
+        };
+        assert_eq!(overlapping.rev().collect::<Vec<_>>(), ["a", ""]);
+    }
+
+    #[test]
+    pub fn splitn_limits() {
+        use super::splitn;
+
+        let s = "a,b,c,d";
+        assert_eq!(splitn(s, 0, ',').collect::<Vec<_>>(), Vec::<&str>::new());
+        assert_eq!(splitn(s, 1, ',').collect::<Vec<_>>(), ["a,b,c,d"]);
+        assert_eq!(splitn(s, 2, ',').collect::<Vec<_>>(), ["a", "b,c,d"]);
+        assert_eq!(splitn(s, 10, ',').collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+        assert_eq!(
+            splitn("k = v = w", 2, " = ").collect::<Vec<_>>(),
+            ["k", "v = w"]
+        );
+        assert_eq!(splitn("", 3, ',').collect::<Vec<_>>(), [""]);
+    }
+
+    #[test]
+    pub fn custom_delimiter() {
+        use super::{Delimiter, StrSplit};
+
+        // Any run of whitespace.
+        struct Whitespace;
+
+        impl Delimiter for Whitespace {
+            fn find_next(&self, haystack: &str) -> Option<(usize, usize)> {
+                let start = haystack.find(char::is_whitespace)?;
+                let end = haystack[start..]
+                    .find(|c: char| !c.is_whitespace())
+                    .map_or(haystack.len(), |len| start + len);
+                Some((start, end))
+            }
+        }
+
+        let s = "a  b\t\n c";
+        assert_eq!(
+            StrSplit::new(s, Whitespace).collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        // The default `find_prev` keeps `next_back` consistent.
+        assert_eq!(
+            StrSplit::new(s, Whitespace).rev().collect::<Vec<_>>(),
+            ["c", "b", "a"]
+        );
+
+        let digits = |haystack: &str| {
+            let start = haystack.find(|c: char| c.is_ascii_digit())?;
+            Some((start, start + 1))
+        };
+        assert_eq!(
+            StrSplit::new("x1y2z", digits).collect::<Vec<_>>(),
+            ["x", "y", "z"]
+        );
+        assert_eq!(digits.find_prev("x1y2z"), Some((3, 4)));
+    }
+
     #[test]
     pub fn count() {
         let v = vec![vec![1, 2, 3], vec![4, 5, 6]];
@@ -340,6 +917,154 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_my_unzip_partition() {
+        let (letters, numbers) = [('a', 1), ('b', 2), ('c', 3)].into_iter().my_unzip();
+        assert_eq!(&*letters, &['a', 'b', 'c']);
+        assert_eq!(&*numbers, &[1, 2, 3]);
+
+        let (even, odd) = (1..=6).my_partition(|x| x % 2 == 0);
+        assert_eq!(&*even, &[2, 4, 6]);
+        assert_eq!(&*odd, &[1, 3, 5]);
+
+        let (empty_a, empty_b) = std::iter::empty::<(i32, i32)>().my_unzip();
+        assert!(empty_a.is_empty() && empty_b.is_empty());
+    }
+
+    #[test]
+    fn test_my_min_max() {
+        assert_eq!([3, 1, 4, 1, 5].into_iter().my_min(), Some(1));
+        assert_eq!([3, 1, 4, 1, 5].into_iter().my_max(), Some(5));
+        assert_eq!(std::iter::empty::<i32>().my_min(), None);
+        assert_eq!(std::iter::empty::<i32>().my_max(), None);
+
+        // Ties: min keeps the first, max the last, like `Iterator`.
+        #[derive(Debug)]
+        struct Tagged(i32, char);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+        let tagged = || {
+            [
+                Tagged(1, 'a'),
+                Tagged(0, 'b'),
+                Tagged(1, 'c'),
+                Tagged(0, 'd'),
+            ]
+        };
+        assert_eq!(tagged().into_iter().my_min().unwrap().1, 'b');
+        assert_eq!(tagged().into_iter().my_max().unwrap().1, 'c');
+    }
+
+    #[test]
+    fn test_my_sum_product() {
+        assert_eq!((1..=4).my_sum::<i32>(), 10);
+        assert_eq!((1..=4).my_product::<i32>(), 24);
+        assert_eq!(std::iter::empty::<i32>().my_sum::<i32>(), 0);
+        assert_eq!(std::iter::empty::<i32>().my_product::<i32>(), 1);
+        assert_eq!([1.5, 2.5].iter().my_sum::<f64>(), 4.0);
+    }
+
+    #[test]
+    fn test_my_cloned_copied() {
+        let words = [String::from("a"), String::from("b")];
+        let mut iter = words.iter().my_cloned();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.next_back(), Some(String::from("b")));
+        assert_eq!(iter.next(), Some(String::from("a")));
+        assert_eq!(iter.next(), None);
+
+        let numbers = [1, 2, 3];
+        let copied: Vec<i32> = numbers.iter().my_copied().collect();
+        assert_eq!(copied, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_my_cycle() {
+        let cycled: Vec<i32> = [1, 2, 3].into_iter().my_cycle().take(7).collect();
+        assert_eq!(cycled, [1, 2, 3, 1, 2, 3, 1]);
+        assert_eq!([1].iter().my_cycle().size_hint(), (usize::MAX, None));
+
+        let mut empty = std::iter::empty::<i32>().my_cycle();
+        assert_eq!(empty.size_hint(), (0, Some(0)));
+        assert_eq!(empty.next(), None);
+    }
+
+    #[test]
+    fn test_my_rev() {
+        let mut iter = [1, 2, 3, 4].into_iter().my_rev();
+        assert_eq!(iter.next(), Some(4));
+        assert_eq!(iter.next_back(), Some(1));
+        assert_eq!(iter.collect::<Vec<_>>(), [3, 2]);
+        // Reversing twice is the identity.
+        let twice: Vec<_> = (0..4).my_rev().my_rev().collect();
+        assert_eq!(twice, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut chunks = (1..=7).chunks(3);
+        assert_eq!(chunks.size_hint(), (3, Some(3)));
+        let first: crate::vec::Vec<i32> = chunks.next().unwrap();
+        assert_eq!(&*first, &[1, 2, 3]);
+        assert_eq!(&*chunks.next().unwrap(), &[4, 5, 6]);
+        assert_eq!(&*chunks.next().unwrap(), &[7]);
+        assert!(chunks.next().is_none());
+
+        let exact: Vec<_> = (0..4).chunks(2).map(|c| c.len()).collect();
+        assert_eq!(exact, [2, 2]);
+        assert!(std::iter::empty::<i32>().chunks(2).next().is_none());
+
+        // The chunks own their items.
+        let words = ["a", "b", "c"].map(String::from);
+        let owned: Vec<_> = words.into_iter().chunks(2).collect();
+        assert_eq!(&*owned[1], &[String::from("c")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be non-zero")]
+    fn test_chunks_zero() {
+        let _ = (0..4).chunks(0);
+    }
+
+    #[test]
+    fn test_take_while_inclusive() {
+        let mut iter = [1, 2, 0, 3].into_iter().take_while_inclusive(|&n| n != 0);
+        assert_eq!(iter.size_hint(), (1, Some(4)));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.next(), None);
+
+        // No item fails: everything is yielded.
+        let all: Vec<_> = (0..3).take_while_inclusive(|_| true).collect();
+        assert_eq!(all, [0, 1, 2]);
+        // The very first fails: only it is yielded.
+        let first: Vec<_> = (0..3).take_while_inclusive(|_| false).collect();
+        assert_eq!(first, [0]);
+
+        let line: String = "GET /\nHost"
+            .chars()
+            .take_while_inclusive(|&c| c != '\n')
+            .collect();
+        assert_eq!(line, "GET /\n");
+    }
+
     #[test]
     fn test_my_map() {
         let v = vec![1, 2, 3];
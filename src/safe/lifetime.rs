@@ -10,6 +10,33 @@ pub fn str_tok<'s>(s: &mut &'s str, delim: char) -> Option<&'s str> {
     }
 }
 
+/// Iterator form of [`str_tok`], created by [`tokenize`].
+pub struct StrTokenizer<'s> {
+    remaining: &'s str,
+    delimiter: char,
+}
+
+/// Yields the pieces of `s` between `delimiter`s, like calling [`str_tok`]
+/// in a loop. Iteration ends once nothing is left, so empty input yields no
+/// tokens and a trailing delimiter does not add an empty last one.
+pub fn tokenize(s: &str, delimiter: char) -> StrTokenizer<'_> {
+    StrTokenizer {
+        remaining: s,
+        delimiter,
+    }
+}
+
+impl<'s> Iterator for StrTokenizer<'s> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        str_tok(&mut self.remaining, self.delimiter)
+    }
+}
+
 ///```compile_fail
 ///
 /// fn make_static(_s: &'static str) {}
@@ -41,4 +68,16 @@ mod tests {
         assert_eq!(ret, "test");
         assert_eq!(token, "");
     }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens: Vec<_> = tokenize("hello,world,,test", ',').collect();
+        assert_eq!(tokens, ["hello", "world", "", "test"]);
+
+        assert_eq!(tokenize("a,b,", ',').count(), 2);
+        assert_eq!(tokenize("", ',').next(), None);
+
+        let lengths: Vec<_> = tokenize("one two three", ' ').map(str::len).collect();
+        assert_eq!(lengths, [3, 3, 5]);
+    }
 }
@@ -0,0 +1,243 @@
+use std::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicU64, Ordering, fence},
+};
+
+/// A sequence lock for small `Copy` data.
+///
+/// Unlike [`RwLock`](crate::rw_lock::RwLock), readers never write to shared
+/// memory: they copy the value optimistically and retry if a writer was active
+/// during the copy. Writers never wait for readers, only for each other.
+///
+/// The sequence counter is even when the data is stable and odd while a
+/// writer is inside `write_with`.
+///
+/// The optimistic copy races with the writer by design; a torn copy is always
+/// detected and thrown away, which is why `T` must be `Copy` (no destructor,
+/// no invariants a torn value could break before it is discarded). Under the
+/// Rust memory model this is still formally a data race, so Miri reports the
+/// concurrent tests.
+pub struct SeqLock<T: Copy> {
+    sequence: AtomicU64,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn read(&self) -> T {
+        loop {
+            if let Some(value) = self.try_read() {
+                return value;
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Makes a single read attempt, returning `None` if a writer was active.
+    ///
+    /// Both counter loads are `Relaxed`; the two acquire fences do the
+    /// ordering instead. The first pairs with the writer's final `Release`
+    /// store so the copy sees that write, and the second keeps the copy from
+    /// sinking below the re-check.
+    pub fn try_read(&self) -> Option<T> {
+        let before = self.sequence.load(Ordering::Relaxed);
+        if before % 2 == 1 {
+            return None;
+        }
+        fence(Ordering::Acquire);
+
+        let value = unsafe { std::ptr::read_volatile(self.data.get()) };
+
+        fence(Ordering::Acquire);
+        let after = self.sequence.load(Ordering::Relaxed);
+        (before == after).then_some(value)
+    }
+
+    pub fn write(&self, value: T) {
+        self.write_with(|v| *v = value);
+    }
+
+    pub fn write_with(&self, f: impl FnOnce(&mut T)) {
+        // Writers exclude each other by moving the counter from even to odd.
+        let mut seq = self.sequence.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                seq = self.sequence.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.sequence.compare_exchange_weak(
+                seq,
+                seq + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => seq = actual,
+            }
+        }
+        // Makes the counter even again when dropped, even if `f` panics.
+        // The data is then still the old value, since `f` only sees a copy.
+        let _unlock = Unlock {
+            sequence: &self.sequence,
+            next: seq + 2,
+        };
+        // Readers that observe any of the writes below must also observe the
+        // odd counter.
+        fence(Ordering::Release);
+
+        // Mutate a copy so `f` never holds a reference that readers race with.
+        let mut value = unsafe { std::ptr::read_volatile(self.data.get()) };
+        f(&mut value);
+        unsafe { std::ptr::write_volatile(self.data.get(), value) };
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+struct Unlock<'a> {
+    sequence: &'a AtomicU64,
+    next: u64,
+}
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.sequence.store(self.next, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    #[test]
+    fn test_single_thread() {
+        let lock = SeqLock::new((1, 2));
+        assert_eq!(lock.read(), (1, 2));
+
+        lock.write_with(|v| v.0 = 10);
+        assert_eq!(lock.read(), (10, 2));
+        assert_eq!(lock.into_inner(), (10, 2));
+    }
+
+    #[test]
+    fn test_try_read_during_write() {
+        let lock = SeqLock::new(1);
+        lock.write_with(|v| {
+            // The counter is odd while the writer runs.
+            assert_eq!(lock.try_read(), None);
+            *v = 2;
+        });
+        assert_eq!(lock.try_read(), Some(2));
+        lock.write(3);
+        assert_eq!(lock.read(), 3);
+    }
+
+    #[test]
+    fn test_panicking_writer() {
+        let lock = SeqLock::new(1);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            lock.write_with(|v| {
+                *v = 2;
+                panic!("writer failed");
+            })
+        }));
+        assert!(result.is_err());
+
+        // The lock is released and the half-done write is dropped.
+        assert_eq!(lock.try_read(), Some(1));
+        assert_eq!(lock.read(), 1);
+        lock.write(3);
+        assert_eq!(lock.read(), 3);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "the optimistic read is a data race by design")]
+    fn test_sensor_readings() {
+        #[derive(Clone, Copy, Debug)]
+        struct Reading {
+            timestamp: u64,
+            x: f64,
+            y: f64,
+            z: f64,
+        }
+
+        let lock = SeqLock::new(Reading {
+            timestamp: 0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        });
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for t in 1..=50_000u64 {
+                    let v = t as f64;
+                    lock.write(Reading {
+                        timestamp: t,
+                        x: v,
+                        y: -v,
+                        z: v * 2.0,
+                    });
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+
+            for _ in 0..3 {
+                s.spawn(|| {
+                    while !done.load(Ordering::Relaxed) {
+                        let r = lock.read();
+                        let v = r.timestamp as f64;
+                        assert_eq!((r.x, r.y, r.z), (v, -v, v * 2.0), "torn read: {r:?}");
+                    }
+                });
+            }
+        });
+
+        assert_eq!(lock.read().timestamp, 50_000);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "the optimistic read is a data race by design")]
+    fn test_no_torn_reads() {
+        let lock = SeqLock::new([0u64; 4]);
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=100_000 {
+                    lock.write_with(|v| *v = [i; 4]);
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut last = 0;
+                    while !done.load(Ordering::Relaxed) {
+                        let v = lock.read();
+                        assert!(v.iter().all(|&x| x == v[0]), "torn read: {v:?}");
+                        assert!(v[0] >= last);
+                        last = v[0];
+                    }
+                });
+            }
+        });
+
+        assert_eq!(lock.read(), [100_000; 4]);
+    }
+}
@@ -0,0 +1,273 @@
+use std::{cmp::Ordering, marker::PhantomData, ptr::NonNull};
+
+const MAX_LEVEL: usize = 16;
+
+type Link<K, V> = Option<NonNull<SkipNode<K, V>>>;
+
+struct SkipNode<K, V> {
+    key: K,
+    value: V,
+    // Only the first `level` entries are ever linked.
+    forward: [Link<K, V>; MAX_LEVEL],
+}
+
+/// A sorted map built from stacked linked lists.
+///
+/// Level 0 links every node in key order; each higher level links a random
+/// half of the level below it, so searches skip ahead from the top level and
+/// take O(log n) steps on average.
+pub struct SkipList<K: Ord, V> {
+    head: [Link<K, V>; MAX_LEVEL],
+    // Number of levels currently in use.
+    level: usize,
+    len: usize,
+    rng: u64,
+    _marker: PhantomData<Box<SkipNode<K, V>>>,
+}
+
+unsafe impl<K: Ord + Send, V: Send> Send for SkipList<K, V> {}
+unsafe impl<K: Ord + Sync, V: Sync> Sync for SkipList<K, V> {}
+
+impl<K: Ord, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        Self {
+            head: [None; MAX_LEVEL],
+            level: 0,
+            len: 0,
+            rng: 0x2545_f491_4f6c_dd1d,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // xorshift64: each extra level is kept with probability 1/2.
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    // For every level, the forward array whose entry at that level is the
+    // last link before `key`.
+    fn predecessors(&mut self, key: &K) -> [*mut [Link<K, V>; MAX_LEVEL]; MAX_LEVEL] {
+        let head: *mut [Link<K, V>; MAX_LEVEL] = &mut self.head;
+        let mut update = [head; MAX_LEVEL];
+        let mut forward = head;
+        for level in (0..self.level).rev() {
+            unsafe {
+                while let Some(next) = (*forward)[level] {
+                    if (*next.as_ptr()).key >= *key {
+                        break;
+                    }
+                    forward = &mut (*next.as_ptr()).forward;
+                }
+            }
+            update[level] = forward;
+        }
+        update
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut forward = &self.head;
+        for level in (0..self.level).rev() {
+            while let Some(next) = forward[level] {
+                let node = unsafe { next.as_ref() };
+                match node.key.cmp(key) {
+                    Ordering::Less => forward = &node.forward,
+                    Ordering::Equal => return Some(&node.value),
+                    Ordering::Greater => break,
+                }
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts `value` under `key`, returning the old value if the key was
+    /// already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // Drawn before `update` is built: `random_level` borrows all of
+        // `self`, which would invalidate the raw pointers into `head`.
+        let level = self.random_level();
+        let update = self.predecessors(&key);
+        unsafe {
+            if let Some(node) = (*update[0])[0]
+                && (*node.as_ptr()).key == key
+            {
+                return Some(std::mem::replace(&mut (*node.as_ptr()).value, value));
+            }
+
+            // Levels above the current height start from the head, which
+            // `update` already holds for them.
+            self.level = self.level.max(level);
+
+            let node = NonNull::new_unchecked(Box::into_raw(Box::new(SkipNode {
+                key,
+                value,
+                forward: [None; MAX_LEVEL],
+            })));
+            for (i, &prev) in update.iter().enumerate().take(level) {
+                (*node.as_ptr()).forward[i] = (*prev)[i];
+                (*prev)[i] = Some(node);
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let update = self.predecessors(key);
+        let node = unsafe {
+            let node = (*update[0])[0]?;
+            if (*node.as_ptr()).key != *key {
+                return None;
+            }
+            for (i, &prev) in update.iter().enumerate().take(self.level) {
+                if (*prev)[i] != Some(node) {
+                    break;
+                }
+                (*prev)[i] = (*node.as_ptr()).forward[i];
+            }
+            Box::from_raw(node.as_ptr())
+        };
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Iterates over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            next: self.head[0],
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        let mut next = self.head[0];
+        while let Some(node) = next {
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            next = node.forward[0];
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    next: Link<K, V>,
+    _marker: PhantomData<&'a SkipNode<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.next?.as_ref() };
+        self.next = node.forward[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_traversal() {
+        let mut list = SkipList::new();
+        // A permutation of 0..1000.
+        for i in 0..1000u32 {
+            let key = (i * 7919) % 1000;
+            assert_eq!(list.insert(key, key * 2), None);
+        }
+        assert_eq!(list.len(), 1000);
+
+        let keys: std::vec::Vec<_> = list.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, (0..1000).collect::<std::vec::Vec<_>>());
+        assert!(list.iter().all(|(k, v)| *v == k * 2));
+    }
+
+    #[test]
+    fn test_get_insert_remove() {
+        let mut list = SkipList::new();
+        assert_eq!(list.get(&1), None);
+        assert_eq!(list.remove(&1), None);
+
+        list.insert(3, "c");
+        list.insert(1, "a");
+        list.insert(2, "b");
+        assert_eq!(list.insert(2, "B"), Some("b"));
+        assert_eq!(list.get(&2), Some(&"B"));
+        assert!(!list.contains_key(&4));
+
+        assert_eq!(list.remove(&2), Some("B"));
+        assert_eq!(list.remove(&2), None);
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            list.iter().collect::<std::vec::Vec<_>>(),
+            [(&1, &"a"), (&3, &"c")]
+        );
+    }
+
+    #[test]
+    fn test_remove_many() {
+        let mut list = SkipList::new();
+        for i in 0..500 {
+            list.insert(i, i);
+        }
+        for i in (0..500).step_by(2) {
+            assert_eq!(list.remove(&i), Some(i));
+        }
+        assert_eq!(list.len(), 250);
+        assert!((0..500).all(|i| list.contains_key(&i) == (i % 2 == 1)));
+
+        for i in (1..500).step_by(2) {
+            list.remove(&i);
+        }
+        assert!(list.is_empty());
+        assert_eq!(list.level, 0);
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = std::rc::Rc::new(());
+        let mut list = SkipList::new();
+        for i in 0..100 {
+            list.insert(i, value.clone());
+        }
+        list.remove(&50);
+        assert_eq!(std::rc::Rc::strong_count(&value), 100);
+        drop(list);
+        assert_eq!(std::rc::Rc::strong_count(&value), 1);
+    }
+}
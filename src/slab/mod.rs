@@ -0,0 +1,198 @@
+use std::ops::{Index, IndexMut};
+
+use crate::vec::Vec;
+
+enum Slot<T> {
+    Occupied(T),
+    // Index of the next vacant slot, or `entries.len()` at the end of the list.
+    Vacant(usize),
+}
+
+/// Pre-allocated storage with stable `usize` keys, e.g. for mapping epoll
+/// tokens to their handlers.
+///
+/// Removed slots are threaded onto a free list and reused by the next
+/// `insert`, so both operations are O(1) and keys stay valid until removed.
+pub struct Slab<T> {
+    entries: Vec<Slot<T>>,
+    next_free: usize,
+    len: usize,
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(cap),
+            next_free: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The key the next `insert` will return.
+    pub fn vacant_key(&self) -> usize {
+        self.next_free
+    }
+
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.next_free;
+        if key == self.entries.len() {
+            self.entries.push(Slot::Occupied(value));
+            self.next_free = key + 1;
+        } else {
+            match std::mem::replace(&mut self.entries[key], Slot::Occupied(value)) {
+                Slot::Vacant(next) => self.next_free = next,
+                Slot::Occupied(_) => unreachable!("free list points at an occupied slot"),
+            }
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Removes the value at `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not occupied.
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid slab key")
+    }
+
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        let slot = self.entries.get_mut(key)?;
+        if let Slot::Vacant(_) = slot {
+            return None;
+        }
+        match std::mem::replace(slot, Slot::Vacant(self.next_free)) {
+            Slot::Occupied(value) => {
+                self.next_free = key;
+                self.len -= 1;
+                Some(value)
+            }
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: usize) -> Option<&T> {
+        match self.entries.get(key)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.entries.get_mut(key)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    /// Iterates over `(key, value)` pairs in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(key, slot)| match slot {
+                Slot::Occupied(value) => Some((key, value)),
+                Slot::Vacant(_) => None,
+            })
+    }
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, key: usize) -> &Self::Output {
+        self.get(key).expect("invalid slab key")
+    }
+}
+
+impl<T> IndexMut<usize> for Slab<T> {
+    fn index_mut(&mut self, key: usize) -> &mut Self::Output {
+        self.get_mut(key).expect("invalid slab key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut slab = Slab::new();
+        let a = slab.insert("a");
+        let b = slab.insert("b");
+        let c = slab.insert("c");
+        assert_eq!((a, b, c), (0, 1, 2));
+        assert_eq!(slab.len(), 3);
+        assert_eq!(slab[b], "b");
+
+        assert_eq!(slab.remove(b), "b");
+        assert!(!slab.contains(b));
+        assert_eq!(slab.get(b), None);
+        assert_eq!(slab.len(), 2);
+
+        slab[c] = "C";
+        assert_eq!(slab[c], "C");
+    }
+
+    #[test]
+    fn test_reuses_vacant_slots() {
+        let mut slab = Slab::new();
+        for i in 0..5 {
+            slab.insert(i);
+        }
+        slab.remove(1);
+        slab.remove(3);
+
+        // Most recently freed first, then back to appending.
+        assert_eq!(slab.vacant_key(), 3);
+        assert_eq!(slab.insert(30), 3);
+        assert_eq!(slab.insert(10), 1);
+        assert_eq!(slab.insert(5), 5);
+        assert_eq!(
+            slab.iter().collect::<std::vec::Vec<_>>(),
+            [(0, &0), (1, &10), (2, &2), (3, &30), (4, &4), (5, &5)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let mut slab = Slab::new();
+        let key = slab.insert(String::from("x"));
+        assert_eq!(slab.try_remove(key).as_deref(), Some("x"));
+        assert_eq!(slab.try_remove(key), None);
+        assert_eq!(slab.try_remove(100), None);
+        assert!(slab.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid slab key")]
+    fn test_index_vacant() {
+        let mut slab = Slab::new();
+        let key = slab.insert(1);
+        slab.remove(key);
+        let _ = slab[key];
+    }
+}
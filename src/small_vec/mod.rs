@@ -0,0 +1,316 @@
+use std::{
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr,
+};
+
+use crate::vec::{self, Vec};
+
+/// A vector that keeps up to `N` elements inline and moves them to the heap
+/// on the first push that does not fit.
+///
+/// The representation is private: the unsafe code relies on the first `len`
+/// inline slots being initialized, which callers could not be trusted with.
+///
+/// ```compile_fail
+/// use std::mem::MaybeUninit;
+/// use learn_unsafe::small_vec::SmallVec;
+/// let v = SmallVec::<i32, 4>::Inline {
+///     data: [const { MaybeUninit::uninit() }; 4],
+///     len: 4,
+/// };
+/// ```
+pub struct SmallVec<T, const N: usize>(Repr<T, N>);
+
+enum Repr<T, const N: usize> {
+    Inline {
+        data: [MaybeUninit<T>; N],
+        len: usize,
+    },
+    Heap(Vec<T>),
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub const fn new() -> Self {
+        SmallVec(Repr::Inline {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Inline { len, .. } => *len,
+            Repr::Heap(vec) => vec.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn spilled(&self) -> bool {
+        matches!(self.0, Repr::Heap(_))
+    }
+
+    pub fn push(&mut self, value: T) {
+        match &mut self.0 {
+            Repr::Inline { data, len } if *len < N => {
+                data[*len].write(value);
+                *len += 1;
+            }
+            Repr::Inline { data, len } => {
+                let mut vec = Vec::with_capacity((N * 2).max(1));
+                for slot in &data[..*len] {
+                    // SAFETY: the first `len` slots are initialized, and `len`
+                    // is reset below so they are not dropped twice.
+                    vec.push(unsafe { slot.assume_init_read() });
+                }
+                *len = 0;
+                vec.push(value);
+                self.0 = Repr::Heap(vec);
+            }
+            Repr::Heap(vec) => vec.push(value),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.0 {
+            Repr::Inline { len: 0, .. } => None,
+            Repr::Inline { data, len } => {
+                *len -= 1;
+                Some(unsafe { data[*len].assume_init_read() })
+            }
+            Repr::Heap(vec) => vec.pop(),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Repr::Inline { data, len } => unsafe {
+                std::slice::from_raw_parts(data.as_ptr().cast::<T>(), *len)
+            },
+            Repr::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.0 {
+            Repr::Inline { data, len } => unsafe {
+                std::slice::from_raw_parts_mut(data.as_mut_ptr().cast::<T>(), *len)
+            },
+            Repr::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for Repr<T, N> {
+    fn drop(&mut self) {
+        // The heap variant drops its own elements.
+        if let Repr::Inline { data, len } = self {
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                    data.as_mut_ptr().cast::<T>(),
+                    *len,
+                ));
+            }
+        }
+    }
+}
+
+/// Private for the same reason as [`SmallVec`]'s representation.
+///
+/// ```compile_fail
+/// use std::mem::MaybeUninit;
+/// use learn_unsafe::small_vec::IntoIter;
+/// let iter = IntoIter::<i32, 4>::Inline {
+///     data: [const { MaybeUninit::uninit() }; 4],
+///     start: 0,
+///     end: 4,
+/// };
+/// ```
+pub struct IntoIter<T, const N: usize>(IntoIterRepr<T, N>);
+
+enum IntoIterRepr<T, const N: usize> {
+    Inline {
+        data: [MaybeUninit<T>; N],
+        start: usize,
+        end: usize,
+    },
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // `Repr` implements `Drop`, so its fields have to be moved out
+        // through a `ManuallyDrop`.
+        let this = ManuallyDrop::new(self);
+        IntoIter(match &this.0 {
+            Repr::Inline { data, len } => IntoIterRepr::Inline {
+                data: unsafe { ptr::read(data) },
+                start: 0,
+                end: *len,
+            },
+            Repr::Heap(vec) => IntoIterRepr::Heap(unsafe { ptr::read(vec) }.into_iter()),
+        })
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterRepr::Inline { data, start, end } => {
+                if start == end {
+                    return None;
+                }
+                *start += 1;
+                Some(unsafe { data[*start - 1].assume_init_read() })
+            }
+            IntoIterRepr::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IntoIterRepr::Inline { start, end, .. } => (end - start, Some(end - start)),
+            IntoIterRepr::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterRepr::Inline { data, start, end } => {
+                if start == end {
+                    return None;
+                }
+                *end -= 1;
+                Some(unsafe { data[*end].assume_init_read() })
+            }
+            IntoIterRepr::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        if let IntoIterRepr::Inline { .. } = self.0 {
+            for _ in self.by_ref() {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn test_inline() {
+        let mut v: SmallVec<i32, 4> = SmallVec::new();
+        assert!(v.is_empty());
+        v.push(1);
+        v.push(2);
+        v.push(3);
+        assert!(!v.spilled());
+        assert_eq!(v.len(), 3);
+        assert_eq!(&*v, [1, 2, 3]);
+
+        v[0] = 10;
+        assert_eq!(v.pop(), Some(3));
+        assert_eq!(&*v, [10, 2]);
+    }
+
+    #[test]
+    fn test_spill() {
+        let mut v: SmallVec<String, 2> = SmallVec::new();
+        v.push("a".into());
+        v.push("b".into());
+        assert!(!v.spilled());
+        v.push("c".into());
+        assert!(v.spilled());
+        assert_eq!(&*v, ["a", "b", "c"]);
+        assert_eq!(v.pop().as_deref(), Some("c"));
+        assert_eq!(v.len(), 2);
+    }
+
+    #[test]
+    fn test_zero_inline_capacity() {
+        let mut v: SmallVec<u8, 0> = SmallVec::new();
+        assert_eq!(v.pop(), None);
+        v.push(1);
+        assert!(v.spilled());
+        assert_eq!(&*v, [1]);
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = Rc::new(());
+
+        let mut inline: SmallVec<_, 4> = SmallVec::new();
+        inline.push(value.clone());
+        inline.push(value.clone());
+        let mut heap: SmallVec<_, 1> = SmallVec::new();
+        heap.push(value.clone());
+        heap.push(value.clone());
+        assert_eq!(Rc::strong_count(&value), 5);
+
+        drop(inline);
+        drop(heap);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut v: SmallVec<i32, 4> = SmallVec::new();
+        for i in 0..3 {
+            v.push(i);
+        }
+        let mut iter = v.into_iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next_back(), Some(2));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), None);
+
+        let mut v: SmallVec<i32, 1> = SmallVec::new();
+        for i in 0..3 {
+            v.push(i);
+        }
+        assert_eq!(v.into_iter().collect::<std::vec::Vec<_>>(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_into_iter_partial_drop() {
+        let value = Rc::new(());
+        let mut v: SmallVec<_, 4> = SmallVec::new();
+        for _ in 0..3 {
+            v.push(value.clone());
+        }
+        let mut iter = v.into_iter();
+        drop(iter.next());
+        assert_eq!(Rc::strong_count(&value), 3);
+        drop(iter);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -1,34 +1,199 @@
 use std::{
     cell::UnsafeCell,
+    fmt::{Debug, Display},
+    marker::PhantomData,
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicBool, Ordering},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
 };
 
-pub struct SpinLock<T> {
+/// A busy-waiting lock.
+///
+/// The lock is poisoned when a guard is dropped during a panic, since the
+/// data may have been left half-updated. `lock` and `try_lock` panic once the
+/// lock is poisoned, until [`clear_poison`](SpinLock::clear_poison) is called
+/// by whoever has checked or repaired the data. `into_inner` and `get_mut`
+/// ignore the flag.
+///
+/// With `TRACK = true` (see [`SpinLock::tracked`]) the lock also counts how
+/// often `lock` found it taken, to help find hot locks. The default leaves
+/// the counter out entirely, so untracked locks pay nothing for it.
+pub struct SpinLock<T, const TRACK: bool = false>
+where
+    Tracking<TRACK>: ContentionCount,
+{
     locked: AtomicBool,
+    poisoned: AtomicBool,
+    contention: <Tracking<TRACK> as ContentionCount>::Counter,
     data: UnsafeCell<T>,
 }
 
-pub struct SpinLockGuard<'a, T> {
-    lock: &'a SpinLock<T>,
+/// Selects the contention counter of a [`SpinLock`] by its `TRACK`
+/// parameter.
+pub struct Tracking<const TRACK: bool>;
+
+/// Implemented for both values of `TRACK`: an `AtomicU64` for `true`, and
+/// `()` for `false`, so the untracked counter takes no space and recording
+/// into it does nothing.
+pub trait ContentionCount {
+    type Counter;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Self::Counter;
+
+    fn record(counter: &Self::Counter);
 }
 
-impl<'a, T> SpinLockGuard<'a, T> {
-    fn new(lock: &'a SpinLock<T>) -> Self {
+impl ContentionCount for Tracking<true> {
+    type Counter = AtomicU64;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicU64 = AtomicU64::new(0);
+
+    fn record(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl ContentionCount for Tracking<false> {
+    type Counter = ();
+
+    const ZERO: () = ();
+
+    fn record(_: &()) {}
+}
+
+pub struct SpinLockGuard<'a, T, const TRACK: bool = false>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    lock: &'a SpinLock<T, TRACK>,
+    // A guard taken while already unwinding must not poison the lock.
+    panicking: bool,
+}
+
+impl<'a, T, const TRACK: bool> SpinLockGuard<'a, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    fn new(lock: &'a SpinLock<T, TRACK>) -> Self {
         // SAFETY: The lock must be held when creating a guard
         // This ensures that we have exclusive access to the data
-        SpinLockGuard { lock }
+        let guard = SpinLockGuard {
+            lock,
+            panicking: std::thread::panicking(),
+        };
+        // The guard is built first so the panic below still unlocks.
+        if lock.is_poisoned() {
+            panic!("SpinLock poisoned: a thread panicked while holding it");
+        }
+        guard
+    }
+}
+
+fn unlock(locked: &AtomicBool, poisoned: &AtomicBool, panicking: bool) {
+    if !panicking && std::thread::panicking() {
+        poisoned.store(true, Ordering::Relaxed);
+    }
+    // The Release store also publishes the poison flag.
+    locked.store(false, Ordering::Release);
+}
+
+impl<'a, T, const TRACK: bool> SpinLockGuard<'a, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    /// Narrows the guard to a part of the protected data, e.g. one field.
+    ///
+    /// This is an associated function, called as `SpinLockGuard::map(guard,
+    /// ..)`, so it cannot shadow a `map` method on `T`.
+    pub fn map<U, F>(orig: Self, f: F) -> MappedSpinLockGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        // If `f` panics, `orig` is still owned here and unlocks as usual.
+        let data = f(unsafe { &mut *orig.lock.data.get() }) as *mut U;
+        let locked = &orig.lock.locked;
+        let poisoned = &orig.lock.poisoned;
+        let panicking = orig.panicking;
+        // Ownership of the lock moves to the mapped guard.
+        std::mem::forget(orig);
+        MappedSpinLockGuard {
+            locked,
+            poisoned,
+            panicking,
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A guard for part of the data behind a [`SpinLock`], made by
+/// [`SpinLockGuard::map`]. Releases the lock when dropped.
+pub struct MappedSpinLockGuard<'a, U> {
+    locked: &'a AtomicBool,
+    poisoned: &'a AtomicBool,
+    panicking: bool,
+    data: *mut U,
+    _marker: PhantomData<&'a mut U>,
+}
+
+impl<'a, U> MappedSpinLockGuard<'a, U> {
+    pub fn map<V, F>(orig: Self, f: F) -> MappedSpinLockGuard<'a, V>
+    where
+        F: FnOnce(&mut U) -> &mut V,
+    {
+        let data = f(unsafe { &mut *orig.data }) as *mut V;
+        let MappedSpinLockGuard {
+            locked,
+            poisoned,
+            panicking,
+            ..
+        } = orig;
+        std::mem::forget(orig);
+        MappedSpinLockGuard {
+            locked,
+            poisoned,
+            panicking,
+            data,
+            _marker: PhantomData,
+        }
     }
 }
 
-impl<T> Drop for SpinLockGuard<'_, T> {
+impl<U> Deref for MappedSpinLockGuard<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<U> DerefMut for MappedSpinLockGuard<'_, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<U> Drop for MappedSpinLockGuard<'_, U> {
+    fn drop(&mut self) {
+        unlock(self.locked, self.poisoned, self.panicking);
+    }
+}
+
+impl<T, const TRACK: bool> Drop for SpinLockGuard<'_, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
     fn drop(&mut self) {
         // When the guard is dropped, we release the lock
-        self.lock.locked.store(false, Ordering::Release);
+        unlock(&self.lock.locked, &self.lock.poisoned, self.panicking);
     }
 }
 
-impl<T> Deref for SpinLockGuard<'_, T> {
+impl<T, const TRACK: bool> Deref for SpinLockGuard<'_, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -36,7 +201,10 @@ impl<T> Deref for SpinLockGuard<'_, T> {
     }
 }
 
-impl<T> DerefMut for SpinLockGuard<'_, T> {
+impl<T, const TRACK: bool> DerefMut for SpinLockGuard<'_, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { &mut *self.lock.data.get() }
     }
@@ -44,25 +212,73 @@ impl<T> DerefMut for SpinLockGuard<'_, T> {
 
 impl<T> SpinLock<T> {
     pub const fn new(data: T) -> Self {
+        Self::with_counter(data)
+    }
+}
+
+impl<T> SpinLock<T, true> {
+    /// Like [`SpinLock::new`], but counts contention; see
+    /// [`contention_count`](SpinLock::contention_count).
+    pub const fn tracked(data: T) -> Self {
+        Self::with_counter(data)
+    }
+
+    /// How many times `lock` (or `lock_with` and `with_fn`) found the lock
+    /// already taken and had to spin.
+    pub fn contention_count(&self) -> u64 {
+        self.contention.load(Ordering::Relaxed)
+    }
+}
+
+impl<T, const TRACK: bool> SpinLock<T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    const fn with_counter(data: T) -> Self {
         SpinLock {
             locked: AtomicBool::new(false),
+            poisoned: AtomicBool::new(false),
+            contention: <Tracking<TRACK> as ContentionCount>::ZERO,
             data: UnsafeCell::new(data),
         }
     }
 
-    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
-        if self
-            .locked
-            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-            .is_ok()
-        {
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Marks the data as consistent again so `lock` stops panicking.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T, TRACK>> {
+        if self.try_acquire() {
             Some(SpinLockGuard::new(self))
         } else {
             None
         }
     }
 
-    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+    fn try_acquire(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    pub fn lock(&self) -> SpinLockGuard<'_, T, TRACK> {
+        self.spin_acquire();
+        // SAFETY: We have exclusive access to the data while the lock is held
+        SpinLockGuard::new(self)
+    }
+
+    fn spin_acquire(&self) {
+        // A strong exchange, so failing here means the lock really was taken:
+        // a spurious failure of the weak one must not count as contention.
+        if self.try_acquire() {
+            return;
+        }
+        <Tracking<TRACK>>::record(&self.contention);
         while self.locked.load(Ordering::Relaxed) {
             std::hint::spin_loop();
         }
@@ -75,8 +291,23 @@ impl<T> SpinLock<T> {
                 std::hint::spin_loop();
             }
         }
-        // SAFETY: We have exclusive access to the data while the lock is held
-        SpinLockGuard::new(self)
+    }
+
+    /// Runs `f` under the lock without allocating or making system calls.
+    ///
+    /// Acquiring and releasing only touch the lock's atomics, so this is
+    /// usable where the futex [`Mutex`](crate::mutex::Mutex) is not: inside
+    /// a signal handler, or in a global allocator. Unlike `lock` it does not
+    /// check the poison flag, because reporting it means panicking, which
+    /// allocates. A panic in `f` still poisons the lock as usual. Whatever
+    /// `f` itself does is of course up to `f`.
+    pub fn lock_with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.spin_acquire();
+        let mut guard = SpinLockGuard {
+            lock: self,
+            panicking: std::thread::panicking(),
+        };
+        f(&mut *guard)
     }
 
     pub fn with_fn<F, R>(&self, f: F) -> R
@@ -86,10 +317,151 @@ impl<T> SpinLock<T> {
         let mut lock = self.lock();
         f(&mut *lock)
     }
+
+    /// Returns a clone of the data, holding the lock only for the clone.
+    ///
+    /// Like `lock`, this spins forever if the calling thread already holds
+    /// the lock.
+    pub fn snapshot(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    /// No locking is needed: `&mut self` already proves exclusive access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default, const TRACK: bool> Default for SpinLock<T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    fn default() -> Self {
+        Self::with_counter(T::default())
+    }
+}
+
+// Only untracked, like `new`: generic over `TRACK`, `SpinLock::from(x)`
+// couldn't infer it.
+impl<T> From<T> for SpinLock<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<T: Debug, const TRACK: bool> Debug for SpinLock<T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("SpinLock");
+        // Not `try_lock`, which panics on a poisoned lock. The guard still
+        // unlocks if `T::fmt` panics, but doesn't poison: formatting only
+        // reads the data, so it can't leave it half-updated.
+        if self.try_acquire() {
+            let guard = SpinLockGuard {
+                lock: self,
+                panicking: true,
+            };
+            d.field("data", &*guard);
+        } else {
+            d.field("data", &format_args!("<locked>"));
+        }
+        d.field("poisoned", &self.is_poisoned());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug, const TRACK: bool> Debug for SpinLockGuard<'_, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display, const TRACK: bool> Display for SpinLockGuard<'_, T, TRACK>
+where
+    Tracking<TRACK>: ContentionCount,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
 }
 
-unsafe impl<T: Send> Send for SpinLock<T> {}
-unsafe impl<T: Send> Sync for SpinLock<T> {}
+impl<U: Debug> Debug for MappedSpinLockGuard<'_, U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<U: Display> Display for MappedSpinLockGuard<'_, U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T: Send, const TRACK: bool> Send for SpinLock<T, TRACK> where
+    Tracking<TRACK>: ContentionCount
+{
+}
+unsafe impl<T: Send, const TRACK: bool> Sync for SpinLock<T, TRACK> where
+    Tracking<TRACK>: ContentionCount
+{
+}
+
+// Same matrix as `rw_lock::WriteGuard` (`MutexGuard` is the exception, see
+// there): unlocking is a plain store, so a guard may be dropped on any
+// thread. Sending a guard hands over `&mut T` and needs `T: Send`; sharing
+// one only hands out `&T` and needs `T: Sync`. (The auto impls derived from
+// `&SpinLock<T>` would instead make the guard `Sync` for any `T: Send`,
+// sharing a `Cell` across threads.)
+unsafe impl<T: Send, const TRACK: bool> Send for SpinLockGuard<'_, T, TRACK> where
+    Tracking<TRACK>: ContentionCount
+{
+}
+unsafe impl<T: Sync, const TRACK: bool> Sync for SpinLockGuard<'_, T, TRACK> where
+    Tracking<TRACK>: ContentionCount
+{
+}
+unsafe impl<U: Send> Send for MappedSpinLockGuard<'_, U> {}
+unsafe impl<U: Sync> Sync for MappedSpinLockGuard<'_, U> {}
+
+/// A `SpinLockGuard` of a `!Send` type cannot leave its thread.
+///
+/// ```compile_fail
+/// use learn_unsafe::spinlock::SpinLock;
+/// fn is_send<T: Send>(_: T) {}
+/// let lock = SpinLock::new(std::rc::Rc::new(1));
+/// is_send(lock.lock());
+/// ```
+///
+/// A `SpinLockGuard` of a `!Sync` type cannot be shared.
+///
+/// ```compile_fail
+/// use learn_unsafe::spinlock::SpinLock;
+/// fn is_sync<T: Sync>(_: T) {}
+/// let lock = SpinLock::new(std::cell::Cell::new(1));
+/// is_sync(lock.lock());
+/// ```
+///
+/// Nor can a mapped guard pointing at one.
+///
+/// ```compile_fail
+/// use learn_unsafe::spinlock::{SpinLock, SpinLockGuard};
+/// fn is_sync<T: Sync>(_: T) {}
+/// let lock = SpinLock::new((1, std::cell::Cell::new(1)));
+/// is_sync(SpinLockGuard::map(lock.lock(), |v| &mut v.1));
+/// ```
+fn _guard_auto_traits() {}
 
 #[cfg(test)]
 mod tests {
@@ -153,4 +525,258 @@ mod tests {
         assert_eq!(data.len(), 3);
         assert_eq!(data.iter().sum::<i32>(), 6);
     }
+
+    #[test]
+    fn spinlock_contention_count() {
+        let lock = SpinLock::tracked(0);
+        *lock.lock() += 1;
+        lock.with_fn(|data| *data += 1);
+        assert_eq!(lock.contention_count(), 0);
+
+        let guard = lock.lock();
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| *lock.lock() += 1);
+            // Hold the lock until the other thread has found it taken.
+            while lock.contention_count() == 0 {
+                std::thread::yield_now();
+            }
+            drop(guard);
+            waiter.join().unwrap();
+        });
+        assert_eq!(lock.contention_count(), 1);
+        assert_eq!(*lock.lock(), 3);
+
+        // Untracked locks have no counter at all.
+        assert!(size_of::<SpinLock<u64>>() < size_of::<SpinLock<u64, true>>());
+    }
+
+    #[test]
+    fn spinlock_with_fn_multi_threaded() {
+        let lock = SpinLock::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        lock.with_fn(|data| *data += 1);
+                    }
+                });
+            }
+        });
+        assert_eq!(lock.with_fn(|data| *data), 4000);
+    }
+
+    #[test]
+    fn spinlock_into_inner_and_get_mut() {
+        use std::sync::atomic::AtomicUsize;
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted(i32);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut lock = SpinLock::from(Counted(1));
+        lock.get_mut().0 = 2;
+        let inner = lock.into_inner();
+        assert_eq!(inner.0, 2);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+        drop(inner);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        let lock: SpinLock<Vec<i32>> = SpinLock::default();
+        assert!(lock.into_inner().is_empty());
+    }
+
+    #[test]
+    fn spinlock_poison() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let lock = SpinLock::new(vec![1]);
+
+        std::thread::scope(|s| {
+            let result = s
+                .spawn(|| {
+                    let mut data = lock.lock();
+                    data.push(2);
+                    panic!("half-way through an update");
+                })
+                .join();
+            assert!(result.is_err());
+        });
+        assert!(lock.is_poisoned());
+
+        // The lock itself was released, but acquiring it reports the poison.
+        let result = catch_unwind(AssertUnwindSafe(|| lock.lock().len()));
+        assert!(result.is_err());
+        let result = catch_unwind(AssertUnwindSafe(|| lock.try_lock().is_some()));
+        assert!(result.is_err());
+
+        lock.clear_poison();
+        let mut data = lock.lock();
+        assert_eq!(*data, [1, 2]);
+        data.pop();
+    }
+
+    #[test]
+    fn spinlock_poison_through_mapped_guard() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let lock = SpinLock::new((0, 0));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut first = SpinLockGuard::map(lock.lock(), |s| &mut s.0);
+            *first = 1;
+            panic!("oops");
+        }));
+        assert!(result.is_err());
+        assert!(lock.is_poisoned());
+        assert_eq!(lock.into_inner(), (1, 0));
+    }
+
+    #[test]
+    fn spinlock_debug() {
+        let lock = SpinLock::new(vec![1, 2]);
+        assert_eq!(
+            format!("{lock:?}"),
+            "SpinLock { data: [1, 2], poisoned: false, .. }"
+        );
+
+        let guard = lock.lock();
+        assert_eq!(format!("{guard:?}"), "[1, 2]");
+        assert_eq!(
+            format!("{lock:?}"),
+            "SpinLock { data: <locked>, poisoned: false, .. }"
+        );
+        drop(guard);
+
+        let guard = SpinLockGuard::map(lock.lock(), |v| &mut v[0]);
+        assert_eq!(format!("{guard} {guard:?}"), "1 1");
+    }
+
+    #[test]
+    fn spinlock_debug_panic_unlocks() {
+        struct Bad;
+
+        impl Debug for Bad {
+            fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                panic!("bad Debug");
+            }
+        }
+
+        let lock = SpinLock::new(Bad);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| format!("{lock:?}")));
+        assert!(result.is_err());
+        assert!(lock.try_lock().is_some());
+        assert!(!lock.is_poisoned());
+    }
+
+    #[test]
+    fn spinlock_snapshot_waits_for_other_thread() {
+        use std::sync::atomic::AtomicBool;
+
+        let lock = SpinLock::new(String::from("old"));
+        let locked = AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let mut guard = lock.lock();
+                locked.store(true, Ordering::Release);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                *guard = String::from("new");
+            });
+            while !locked.load(Ordering::Acquire) {
+                std::hint::spin_loop();
+            }
+            // Blocks until the writer releases, so it never sees "old".
+            assert_eq!(lock.snapshot(), "new");
+        });
+        assert!(lock.try_lock().is_some());
+    }
+
+    #[test]
+    fn spinlock_guard_send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<SpinLockGuard<'_, i32>>();
+        is_sync::<SpinLockGuard<'_, i32>>();
+        is_send::<MappedSpinLockGuard<'_, i32>>();
+        is_sync::<MappedSpinLockGuard<'_, i32>>();
+
+        // `Cell` is Send but not Sync; `std::sync::MutexGuard` the reverse.
+        is_send::<SpinLockGuard<'_, std::cell::Cell<i32>>>();
+        is_sync::<SpinLockGuard<'_, std::sync::MutexGuard<'_, i32>>>();
+        is_send::<MappedSpinLockGuard<'_, std::cell::Cell<i32>>>();
+    }
+
+    #[test]
+    fn spinlock_guard_dropped_on_other_thread() {
+        let lock = SpinLock::new(0);
+        let mut guard = lock.lock();
+        *guard += 1;
+        std::thread::scope(|s| {
+            s.spawn(move || drop(guard));
+        });
+        assert_eq!(*lock.lock(), 1);
+    }
+
+    #[test]
+    fn spinlock_static() {
+        static COUNTER: SpinLock<u64> = SpinLock::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        COUNTER.lock_with(|n| *n += 1);
+                        *COUNTER.lock() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*COUNTER.lock(), 8000);
+    }
+
+    #[test]
+    fn spinlock_map() {
+        struct State {
+            items: Vec<i32>,
+            name: &'static str,
+        }
+
+        fn push_item(mut items: MappedSpinLockGuard<'_, Vec<i32>>) {
+            items.push(42);
+        }
+
+        let lock = SpinLock::new(State {
+            items: vec![],
+            name: "state",
+        });
+        push_item(SpinLockGuard::map(lock.lock(), |s| &mut s.items));
+
+        let guard = lock.try_lock().expect("mapped guard released the lock");
+        assert_eq!(guard.items, [42]);
+        assert_eq!(guard.name, "state");
+    }
+
+    #[test]
+    fn spinlock_map_twice() {
+        let lock = SpinLock::new((0, vec![1, 2, 3]));
+        {
+            let items = SpinLockGuard::map(lock.lock(), |s| &mut s.1);
+            let mut last = MappedSpinLockGuard::map(items, |v| v.last_mut().unwrap());
+            assert!(lock.try_lock().is_none());
+            *last = 30;
+        }
+        assert_eq!(lock.lock().1, [1, 2, 30]);
+        // Released exactly once: a second locker still gets in and excludes.
+        let guard = lock.lock();
+        assert!(lock.try_lock().is_none());
+        drop(guard);
+        assert!(lock.try_lock().is_some());
+    }
 }
@@ -0,0 +1,192 @@
+use std::{
+    mem::ManuallyDrop,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// A lock-free LIFO stack (Treiber, 1986).
+///
+/// # Known limitations
+///
+/// A naive Treiber stack frees a node as soon as it is popped, which opens
+/// two holes: another thread may still be reading that node's `next` link
+/// (use-after-free), and the allocator may hand the same address back to a
+/// later `push`, so a stale `compare_exchange` on `head` succeeds even though
+/// the stack changed underneath it (the ABA problem).
+///
+/// This implementation sidesteps both by never freeing a popped node while the
+/// stack is alive: popped nodes are moved to a `retired` list and released in
+/// `Drop`. Since an address can never re-enter the stack, ABA cannot happen.
+/// The price is that memory grows with the number of pops; a production
+/// version would use hazard pointers or epoch-based reclamation instead.
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    retired: AtomicPtr<Node<T>>,
+}
+
+struct Node<T> {
+    // Atomic because a node may be relinked into `retired` while a losing
+    // `pop` is still reading it.
+    next: AtomicPtr<Node<T>>,
+    value: ManuallyDrop<T>,
+}
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            value: ManuallyDrop::new(value),
+        }));
+        push_node(&self.head, node);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            // SAFETY: nodes are only freed in `Drop`, so `head` is still
+            // allocated even if another thread popped it in the meantime.
+            let next = unsafe { (*head).next.load(Ordering::Relaxed) };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(new_head) => head = new_head,
+            }
+        }
+        // SAFETY: the successful CAS gives us exclusive ownership of the value.
+        let value = unsafe { ManuallyDrop::take(&mut (*head).value) };
+        push_node(&self.retired, head);
+        Some(value)
+    }
+}
+
+fn push_node<T>(list: &AtomicPtr<Node<T>>, node: *mut Node<T>) {
+    let mut head = list.load(Ordering::Relaxed);
+    loop {
+        unsafe { (*node).next.store(head, Ordering::Relaxed) };
+        match list.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(new_head) => head = new_head,
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            unsafe { ManuallyDrop::drop(&mut node.value) };
+            cur = *node.next.get_mut();
+        }
+
+        // Retired nodes had their value moved out by `pop`.
+        let mut cur = *self.retired.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = *node.next.get_mut();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let stack = TreiberStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_drop_remaining() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct D;
+        impl Drop for D {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let stack = TreiberStack::new();
+        for _ in 0..5 {
+            stack.push(D);
+        }
+        drop(stack.pop());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        drop(stack);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_multi_thread() {
+        let stack = TreiberStack::new();
+        let popped = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let stack = &stack;
+                s.spawn(move || {
+                    for i in 0..1000 {
+                        stack.push(t * 1000 + i);
+                    }
+                });
+            }
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while popped.load(Ordering::Relaxed) < 4000 {
+                        if let Some(v) = stack.pop() {
+                            sum.fetch_add(v, Ordering::Relaxed);
+                            popped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(stack.is_empty());
+        assert_eq!(sum.load(Ordering::Relaxed), (0..4000).sum());
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    thread::{self, ThreadId},
+};
+
+use crate::mutex::Mutex;
+
+/// A per-object, per-thread value: each thread that calls `get_or_init` gets
+/// its own `T`, and all of them are dropped together with the `ThreadLocal`.
+///
+/// Unlike `thread_local!`, this lives in a normal (non-static) value. Thread
+/// ids are never reused, so an exited thread's value stays until the
+/// `ThreadLocal` itself is dropped.
+pub struct ThreadLocal<T> {
+    // Boxed so a value doesn't move when the map grows: `get_or_init` hands
+    // out references that outlive the lock.
+    map: Mutex<HashMap<ThreadId, Box<T>>>,
+}
+
+// `Sync` follows from the auto impls (the map only needs `T: Send`): a value
+// is only ever borrowed by the thread that created it, so `T: Sync` is not
+// required, and it is dropped by whichever thread drops the `ThreadLocal`.
+
+impl<T> ThreadLocal<T> {
+    pub fn new() -> Self {
+        Self {
+            map: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the current thread's value, if it has one.
+    pub fn get(&self) -> Option<&T> {
+        let map = self.map.lock();
+        let value: *const T = &**map.get(&thread::current().id())?;
+        // SAFETY: the box is only freed by `&mut self` methods or drop, and
+        // only this thread ever touches its entry.
+        Some(unsafe { &*value })
+    }
+
+    /// Returns the current thread's value, creating it with `f` first if
+    /// needed. `f` runs without the lock held, so it may use `self`.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        let value = Box::new(f());
+        let mut map = self.map.lock();
+        // Only this thread inserts under its own id, so the entry is still
+        // vacant unless `f` itself initialized it, in which case the first
+        // value wins and ours is dropped.
+        let value: *const T = &**map.entry(thread::current().id()).or_insert(value);
+        unsafe { &*value }
+    }
+}
+
+impl<T> Default for ThreadLocal<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_one_value_per_thread() {
+        let tls = ThreadLocal::new();
+        let inits = AtomicUsize::new(0);
+        let init = || {
+            inits.fetch_add(1, Ordering::Relaxed);
+            Cell::new(0)
+        };
+
+        assert!(tls.get().is_none());
+        tls.get_or_init(init).set(10);
+        assert_eq!(tls.get_or_init(init).get(), 10);
+
+        std::thread::scope(|s| {
+            for i in 1..=3 {
+                let tls = &tls;
+                s.spawn(move || {
+                    let cell = tls.get_or_init(init);
+                    assert_eq!(cell.get(), 0);
+                    cell.set(i);
+                    assert_eq!(tls.get().map(Cell::get), Some(i));
+                });
+            }
+        });
+
+        assert_eq!(inits.load(Ordering::Relaxed), 4);
+        assert_eq!(tls.get().map(Cell::get), Some(10));
+        assert_eq!(tls.map.lock().len(), 4);
+    }
+
+    #[test]
+    fn test_references_survive_growth() {
+        let tls = ThreadLocal::new();
+        let mine = tls.get_or_init(|| String::from("main"));
+        std::thread::scope(|s| {
+            for _ in 0..64 {
+                s.spawn(|| {
+                    tls.get_or_init(String::new);
+                });
+            }
+        });
+        assert_eq!(mine, "main");
+    }
+
+    #[test]
+    fn test_reentrant_init() {
+        let tls = ThreadLocal::new();
+        let value = tls.get_or_init(|| *tls.get_or_init(|| 1) + 1);
+        assert_eq!(*value, 1);
+    }
+}
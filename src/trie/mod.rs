@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, mem};
+
+struct TrieNode<V> {
+    // Sparse and ordered, so prefix iteration comes out sorted.
+    children: BTreeMap<u8, Box<TrieNode<V>>>,
+    // The full key is kept next to the value so iteration can lend it out.
+    value: Option<(Box<[u8]>, V)>,
+}
+
+impl<V> TrieNode<V> {
+    fn new() -> Self {
+        Self {
+            children: BTreeMap::new(),
+            value: None,
+        }
+    }
+}
+
+// The default drop recurses once per level, which a long enough key turns
+// into a stack overflow. Detach the children onto a heap stack instead, so
+// every node is dropped with no children left.
+impl<V> Drop for TrieNode<V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = mem::take(&mut self.children).into_values().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(mem::take(&mut node.children).into_values());
+        }
+    }
+}
+
+/// A map from byte strings to values that supports prefix queries.
+pub struct Trie<V> {
+    root: TrieNode<V>,
+    len: usize,
+}
+
+impl<V> Trie<V> {
+    pub fn new() -> Self {
+        Self {
+            root: TrieNode::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `value` under `key`, returning the old value if there was one.
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for &byte in key {
+            node = node
+                .children
+                .entry(byte)
+                .or_insert_with(|| Box::new(TrieNode::new()));
+        }
+        let old = node.value.replace((key.into(), value));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old.map(|(_, v)| v)
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&TrieNode<V>> {
+        let mut node = &self.root;
+        for byte in key {
+            node = node.children.get(byte)?;
+        }
+        Some(node)
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.find(key)?.value.as_ref().map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes `key`, pruning nodes that no longer lead to any value.
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        // The depth of the deepest node on the path that has to stay: the
+        // root, or one that still leads somewhere else or holds a value.
+        let mut keep = 0;
+        let mut node = &mut self.root;
+        for (depth, byte) in key.iter().enumerate() {
+            if node.value.is_some() || node.children.len() > 1 {
+                keep = depth;
+            }
+            node = node.children.get_mut(byte)?;
+        }
+        let (_, value) = node.value.take()?;
+        self.len -= 1;
+
+        // Everything below `keep` only led to the removed value.
+        if node.children.is_empty()
+            && let Some(byte) = key.get(keep)
+        {
+            let mut node = &mut self.root;
+            for byte in &key[..keep] {
+                node = node
+                    .children
+                    .get_mut(byte)
+                    .expect("on the path just walked");
+            }
+            node.children.remove(byte);
+        }
+        Some(value)
+    }
+
+    /// Iterates over every entry whose key starts with `prefix`, in
+    /// lexicographic key order.
+    pub fn prefix_iter<'a>(&'a self, prefix: &[u8]) -> impl Iterator<Item = (&'a [u8], &'a V)> {
+        PrefixIter {
+            stack: self.find(prefix).into_iter().collect(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &V)> {
+        self.prefix_iter(&[])
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Depth-first walk; children are pushed in reverse so the smallest byte is
+// visited first.
+struct PrefixIter<'a, V> {
+    stack: Vec<&'a TrieNode<V>>,
+}
+
+impl<'a, V> Iterator for PrefixIter<'a, V> {
+    type Item = (&'a [u8], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            self.stack
+                .extend(node.children.values().rev().map(|child| &**child));
+            if let Some((key, value)) = &node.value {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 12] = [
+        "car", "card", "care", "careful", "cart", "cat", "dog", "do", "door", "dot", "a", "an",
+    ];
+
+    fn dictionary() -> Trie<usize> {
+        let mut trie = Trie::new();
+        for (i, word) in WORDS.iter().enumerate() {
+            assert_eq!(trie.insert(word.as_bytes(), i), None);
+        }
+        trie
+    }
+
+    fn words<'a>(iter: impl Iterator<Item = (&'a [u8], &'a usize)>) -> Vec<&'a str> {
+        iter.map(|(k, _)| std::str::from_utf8(k).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_get() {
+        let trie = dictionary();
+        assert_eq!(trie.len(), WORDS.len());
+        assert_eq!(trie.get(b"care"), Some(&2));
+        assert_eq!(trie.get(b"do"), Some(&7));
+        // A prefix of stored words is not itself a key.
+        assert_eq!(trie.get(b"ca"), None);
+        assert_eq!(trie.get(b"cards"), None);
+        assert!(!trie.contains_key(b""));
+    }
+
+    #[test]
+    fn test_prefix_iter() {
+        let trie = dictionary();
+        assert_eq!(
+            words(trie.prefix_iter(b"car")),
+            ["car", "card", "care", "careful", "cart"]
+        );
+        assert_eq!(words(trie.prefix_iter(b"do")), ["do", "dog", "door", "dot"]);
+        assert_eq!(words(trie.prefix_iter(b"x")), Vec::<&str>::new());
+        assert_eq!(trie.iter().count(), WORDS.len());
+        assert_eq!(words(trie.iter()).first(), Some(&"a"));
+    }
+
+    #[test]
+    fn test_insert_replace_and_remove() {
+        let mut trie = dictionary();
+        assert_eq!(trie.insert(b"cat", 100), Some(5));
+        assert_eq!(trie.len(), WORDS.len());
+
+        assert_eq!(trie.remove(b"careful"), Some(3));
+        assert_eq!(trie.remove(b"careful"), None);
+        assert_eq!(trie.remove(b"ca"), None);
+        assert_eq!(trie.get(b"care"), Some(&2));
+
+        assert_eq!(trie.remove(b"car"), Some(0));
+        assert_eq!(words(trie.prefix_iter(b"car")), ["card", "care", "cart"]);
+        assert_eq!(trie.len(), WORDS.len() - 2);
+    }
+
+    #[test]
+    fn test_long_key() {
+        // Deep enough to overflow the stack if anything recursed per byte.
+        let key = vec![b'x'; 100_000];
+        let mut trie = Trie::new();
+        trie.insert(&key, 1);
+        trie.insert(&key[..10], 2);
+        assert_eq!(trie.get(&key), Some(&1));
+        assert_eq!(trie.prefix_iter(&key[..5]).count(), 2);
+
+        assert_eq!(trie.remove(&key), Some(1));
+        assert_eq!(trie.get(&key[..10]), Some(&2));
+        assert!(trie.find(&key[..11]).is_none());
+
+        trie.insert(&key, 3);
+        drop(trie);
+    }
+
+    #[test]
+    fn test_remove_prunes() {
+        let mut trie = Trie::new();
+        trie.insert(b"abc", ());
+        trie.remove(b"abc");
+        assert!(trie.is_empty());
+        assert!(trie.root.children.is_empty());
+
+        trie.insert(b"", ());
+        assert_eq!(trie.get(b""), Some(&()));
+    }
+}
@@ -76,31 +76,42 @@ impl<T> RawVec<T> {
         }
     }
 
+    // Amortized growth for `push` and `insert`. Growing by 1.5x rather than
+    // 2x still gives O(1) amortized pushes, but wastes at most a third of the
+    // buffer instead of half. The first allocation holds 4 elements so small
+    // vecs don't reallocate on each of their first few pushes.
     fn grow(&mut self) {
         assert!(
             std::mem::size_of::<T>() != 0,
             "Capacity overflow for zero-sized type"
         );
-        let (new_cap, new_layout) = if self.cap == 0 {
-            (1, std::alloc::Layout::array::<T>(1).unwrap())
+        self.grow_amortized(self.cap + 1);
+    }
+
+    // Grows to at least `needed` elements, but by no less than `grow` would,
+    // so that repeated small extends stay O(1) amortized too.
+    fn grow_amortized(&mut self, needed: usize) {
+        let new_cap = if self.cap == 0 {
+            4
         } else {
-            let new_cap = self.cap << 1;
-            /***
-             * `Layout::array` is used to create a layout for an array of `T` with `new_cap` elements.
-             * This is necessary because the size of the allocation needs to account for the number of elements
-             * being allocated, not just the size of a single element.
-             * If `new_cap` is 0, it will panic because `Layout::array` cannot create a layout for an array of zero elements.
-             * The `unwrap()` is used to handle the case where the layout cannot be created, which should not happen in this context
-             * since `new_cap` is guaranteed to be at least 1.
-             * This ensures that the allocation is always valid and can hold at least one element of type `T`.
-             *
-             * `Layout::array` will check the space allocated if smaller than `usize::MAX` and will panic if it is not.
-             * But because old_layout.size() <= isize::MAX as usize, we can safely assume that the new layout will also be valid.
-             * so we can safely use `unwrap()` here.
-             */
-            let new_layout = std::alloc::Layout::array::<T>(new_cap).unwrap();
-            (new_cap, new_layout)
+            self.cap
+                .checked_add(self.cap / 2)
+                .expect("Capacity overflow")
         };
+        self.grow_to(new_cap.max(needed));
+    }
+
+    // Reallocates to exactly `target` elements.
+    fn grow_to(&mut self, target: usize) {
+        debug_assert!(std::mem::size_of::<T>() != 0);
+        debug_assert!(target > self.cap, "grow_to must grow");
+        /***
+         * `Layout::array` fails if the total size overflows `isize::MAX`,
+         * which we report as a capacity overflow; the assert below keeps the
+         * same message for the (unreachable on most targets) edge case where
+         * it does not.
+         */
+        let new_layout = Layout::array::<T>(target).expect("Capacity overflow");
 
         assert!(
             new_layout.size() <= isize::MAX as usize,
@@ -123,7 +134,7 @@ impl<T> RawVec<T> {
             Some(ptr) => ptr,
             None => std::alloc::handle_alloc_error(new_layout),
         };
-        self.cap = new_cap;
+        self.cap = target;
     }
 }
 
@@ -158,14 +169,14 @@ impl<T> RawValIter<T> {
 
 #[allow(dead_code)]
 impl<T> Vec<T> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Vec {
             buf: RawVec::new(),
             len: 0,
         }
     }
 
-    fn with_capacity(cap: usize) -> Self {
+    pub fn with_capacity(cap: usize) -> Self {
         Vec {
             buf: RawVec::with_capacity(cap),
             len: 0,
@@ -180,6 +191,27 @@ impl<T> Vec<T> {
         self.buf.cap
     }
 
+    /// Makes room for at least `additional` more elements, allocating
+    /// exactly that much if the current capacity is not enough.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("Capacity overflow");
+        if needed > self.cap() {
+            self.buf.grow_to(needed);
+        }
+    }
+
+    // Like `reserve_exact`, but grows at least as much as `push` would, for
+    // the bulk appends that may be called in a loop.
+    fn reserve_amortized(&mut self, additional: usize) {
+        if std::mem::size_of::<T>() == 0 {
+            return;
+        }
+        let needed = self.len.checked_add(additional).expect("Capacity overflow");
+        if needed > self.cap() {
+            self.buf.grow_amortized(needed);
+        }
+    }
+
     pub fn push(&mut self, value: T) {
         if self.len == self.cap() {
             self.buf.grow();
@@ -239,6 +271,95 @@ impl<T> Vec<T> {
             value
         }
     }
+
+    /// Appends `n` copies of `value`: `n - 1` clones, then `value` itself,
+    /// so it is cloned once less than with `repeat(value).take(n)`. Does
+    /// nothing (and drops `value`) if `n` is zero.
+    pub fn extend_with(&mut self, n: usize, value: T)
+    where
+        T: Clone,
+    {
+        if n == 0 {
+            return;
+        }
+        self.reserve_amortized(n);
+        for _ in 1..n {
+            self.push(value.clone());
+        }
+        self.push(value);
+    }
+
+    // The slice already has these through `Deref`; declaring them here keeps
+    // them visible on `Vec` itself, in docs and to method resolution.
+
+    /// Overwrites every element with a clone of `value`. The length doesn't
+    /// change.
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        (**self).fill(value)
+    }
+
+    /// Overwrites every element with the result of calling `f`, front to back.
+    pub fn fill_with<F: FnMut() -> T>(&mut self, f: F) {
+        (**self).fill_with(f)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        (**self).first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        (**self).last()
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut T> {
+        (**self).first_mut()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        (**self).last_mut()
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        (**self).contains(value)
+    }
+
+    /// The index of the first element matching `predicate`.
+    pub fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().position(predicate)
+    }
+
+    /// Rotates in place so the element at `mid` becomes the first.
+    ///
+    /// # Panics
+    ///
+    /// If `mid > self.len()`.
+    pub fn rotate_left(&mut self, mid: usize) {
+        (**self).rotate_left(mid)
+    }
+
+    /// Rotates in place so the last `k` elements move to the front.
+    ///
+    /// # Panics
+    ///
+    /// If `k > self.len()`.
+    pub fn rotate_right(&mut self, k: usize) {
+        (**self).rotate_right(k)
+    }
+}
+
+impl<T> Default for Vec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> Iterator for RawValIter<T> {
@@ -336,13 +457,7 @@ impl<T> Extend<T> for Vec<T> {
         let iter = iter.into_iter();
         let (lower, _) = iter.size_hint();
 
-        if std::mem::size_of::<T>() != 0 {
-            // TODO: Handle upper bound as well
-            // TODO: single allocation for all elements
-            while self.len() + lower > self.cap() {
-                self.buf.grow();
-            }
-        }
+        self.reserve_amortized(lower);
 
         for item in iter {
             if std::mem::size_of::<T>() != 0 && self.len() == self.cap() {
@@ -356,6 +471,54 @@ impl<T> Extend<T> for Vec<T> {
     }
 }
 
+/// Formats as `[a, b, c]`, using each element's `Display`.
+impl<T: std::fmt::Display> std::fmt::Display for Vec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            std::fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Vec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// Comparisons and hashing go through the slice, so they agree with
+// `std::vec::Vec` (lexicographic order, length-prefixed hash).
+impl<T: PartialEq> PartialEq for Vec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Vec<T> {}
+
+impl<T: PartialOrd> PartialOrd for Vec<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord> Ord for Vec<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: std::hash::Hash> std::hash::Hash for Vec<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
 impl<T> Deref for Vec<T> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
@@ -709,6 +872,131 @@ mod tests {
         vec.buf.grow(); // This should panic due to overflow
     }
 
+    #[test]
+    fn test_growth_factor() {
+        let mut vec = Vec::new();
+        let mut caps = std::vec::Vec::new();
+        for i in 0..100 {
+            vec.push(i);
+            if caps.last() != Some(&vec.cap()) {
+                caps.push(vec.cap());
+            }
+        }
+        assert_eq!(caps, [4, 6, 9, 13, 19, 28, 42, 63, 94, 141]);
+
+        let mut vec = Vec::with_capacity(1);
+        vec.push(1);
+        vec.push(2);
+        assert_eq!(vec.cap(), 2);
+    }
+
+    #[test]
+    fn test_reserve_exact() {
+        let mut vec = Vec::new();
+        vec.reserve_exact(5);
+        assert_eq!(vec.cap(), 5);
+        vec.extend(0..5);
+        assert_eq!(vec.cap(), 5);
+        vec.reserve_exact(2);
+        assert_eq!(vec.cap(), 7);
+        vec.reserve_exact(1);
+        assert_eq!(vec.cap(), 7);
+        assert_eq!(*vec, [0, 1, 2, 3, 4]);
+
+        let mut zst = Vec::new();
+        zst.reserve_exact(10);
+        zst.push(());
+        assert_eq!(zst.cap(), usize::MAX);
+    }
+
+    #[test]
+    fn test_fill_first_last_contains() {
+        let mut v: Vec<i32> = Vec::new();
+        assert_eq!(v.first(), None);
+        assert_eq!(v.last_mut(), None);
+        v.fill(1);
+        assert_eq!(v.len(), 0);
+
+        v.extend([1, 2, 3]);
+        assert_eq!(v.first(), Some(&1));
+        assert_eq!(v.last(), Some(&3));
+        *v.first_mut().unwrap() = 10;
+        *v.last_mut().unwrap() = 30;
+        assert_eq!(&*v, &[10, 2, 30]);
+        assert!(v.contains(&2));
+        assert!(!v.contains(&3));
+
+        v.fill(7);
+        assert_eq!(&*v, &[7, 7, 7]);
+        assert_eq!(v.len(), 3);
+
+        let mut next = 0;
+        v.fill_with(|| {
+            next += 1;
+            next
+        });
+        assert_eq!(&*v, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_extend_with() {
+        let mut v = Vec::new();
+        v.extend_with(3, String::from("a"));
+        assert_eq!(v.len(), 3);
+        assert!(v.cap() >= 3);
+        v.extend_with(0, String::from("never"));
+        v.extend_with(1, String::from("b"));
+        v.extend_with(2, String::from("c"));
+        assert_eq!(&*v, &["a", "a", "a", "b", "c", "c"]);
+
+        // Only `n - 1` clones are made.
+        struct Counted<'a>(&'a std::cell::Cell<usize>);
+        impl Clone for Counted<'_> {
+            fn clone(&self) -> Self {
+                self.0.set(self.0.get() + 1);
+                Counted(self.0)
+            }
+        }
+        let clones = std::cell::Cell::new(0);
+        let mut counted = Vec::new();
+        counted.extend_with(4, Counted(&clones));
+        assert_eq!(counted.len(), 4);
+        assert_eq!(clones.get(), 3);
+
+        let mut zst = Vec::new();
+        zst.extend_with(5, ());
+        assert_eq!(zst.len(), 5);
+    }
+
+    #[test]
+    fn test_position_rotate() {
+        let mut v: Vec<i32> = Vec::new();
+        assert_eq!(v.position(|_| true), None);
+        v.rotate_left(0);
+        v.rotate_right(0);
+
+        v.extend([1, 2, 3, 4, 5]);
+        assert_eq!(v.position(|&x| x > 2), Some(2));
+        assert_eq!(v.position(|&x| x > 5), None);
+
+        v.rotate_left(2);
+        assert_eq!(&*v, &[3, 4, 5, 1, 2]);
+        v.rotate_right(2);
+        assert_eq!(&*v, &[1, 2, 3, 4, 5]);
+        v.rotate_left(5);
+        assert_eq!(&*v, &[1, 2, 3, 4, 5]);
+        v.rotate_right(1);
+        assert_eq!(&*v, &[5, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rotate_out_of_bounds() {
+        let mut v: Vec<i32> = Vec::new();
+        v.extend([1, 2, 3]);
+        v.rotate_left(4);
+    }
+
     #[test]
     fn test_with_capacity() {
         let vec: Vec<i32> = Vec::with_capacity(10);
@@ -744,6 +1032,38 @@ mod tests {
         assert!(vec.cap() >= 3); // Capacity should be at least 3 after grow
     }
 
+    #[test]
+    fn test_over_aligned() {
+        #[repr(align(64))]
+        #[derive(Debug, PartialEq)]
+        struct Aligned(u8);
+
+        let assert_aligned = |vec: &Vec<Aligned>| {
+            for elem in vec.iter() {
+                assert_eq!(elem as *const Aligned as usize % 64, 0);
+            }
+        };
+
+        // Both the first allocation and every realloc have to keep the
+        // alignment.
+        let mut vec = Vec::new();
+        for i in 0..20 {
+            vec.push(Aligned(i));
+            assert_aligned(&vec);
+        }
+        for (i, elem) in vec.iter().enumerate() {
+            assert_eq!(elem.0, i as u8);
+        }
+
+        let mut vec = Vec::with_capacity(3);
+        vec.insert(0, Aligned(1));
+        vec.insert(0, Aligned(0));
+        vec.push(Aligned(2));
+        vec.push(Aligned(3));
+        assert_aligned(&vec);
+        assert_eq!(vec.pop(), Some(Aligned(3)));
+    }
+
     #[test]
     fn test_extend() {
         let mut vec = Vec::new();
@@ -759,6 +1079,27 @@ mod tests {
         assert_eq!(vec[4], 5);
     }
 
+    #[test]
+    fn test_extend_growth_is_amortized() {
+        let mut vec = Vec::new();
+        let mut reallocs = 0;
+        for i in 0..1000 {
+            let cap = vec.cap();
+            vec.extend([i]);
+            if vec.cap() != cap {
+                reallocs += 1;
+            }
+        }
+        // The same 1.5x steps as `push`, not one reallocation per call.
+        assert_eq!(reallocs, 15);
+        assert!((0..1000).eq(vec.iter().copied()));
+
+        // A large enough hint is still allocated in one go.
+        let mut vec: Vec<i32> = Vec::with_capacity(4);
+        vec.extend(0..100);
+        assert_eq!(vec.cap(), 100);
+    }
+
     #[test]
     fn test_extend_empty() {
         let mut vec = Vec::new();
@@ -849,4 +1190,56 @@ mod tests {
     /// assert!(COUNT == 10);
     /// ```
     fn _foo() {}
+
+    #[test]
+    fn test_display() {
+        let mut v = Vec::new();
+        assert_eq!(v.to_string(), "[]");
+        v.push("a");
+        v.push("b");
+        assert_eq!(format!("{v}"), "[a, b]");
+    }
+
+    #[test]
+    fn test_eq_ord_hash() {
+        use std::collections::HashSet;
+        use std::hash::{BuildHasher, RandomState};
+
+        let vec_of = |items: &[i32]| {
+            let mut v = Vec::new();
+            v.extend(items.iter().copied());
+            v
+        };
+
+        assert_eq!(vec_of(&[1, 2]), vec_of(&[1, 2]));
+        assert_ne!(vec_of(&[1, 2]), vec_of(&[1, 2, 3]));
+        assert!(vec_of(&[1, 2]) < vec_of(&[1, 2, 3]));
+        assert!(vec_of(&[1, 3]) > vec_of(&[1, 2, 3]));
+        assert!(Vec::<i32>::default() < vec_of(&[0]));
+
+        let mut sorted = [vec_of(&[2]), vec_of(&[1, 5]), vec_of(&[]), vec_of(&[1])];
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            [vec_of(&[]), vec_of(&[1]), vec_of(&[1, 5]), vec_of(&[2])]
+        );
+
+        // Same hash as the std Vec, so the two can share lookup logic.
+        let state = RandomState::new();
+        assert_eq!(
+            state.hash_one(vec_of(&[1, 2, 3])),
+            state.hash_one(std::vec![1, 2, 3])
+        );
+        let set: HashSet<_> = [vec_of(&[1]), vec_of(&[1]), vec_of(&[2])]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+
+        let floats = {
+            let mut v = Vec::new();
+            v.push(f64::NAN);
+            v
+        };
+        assert_eq!(floats.partial_cmp(&floats), None);
+    }
 }
@@ -0,0 +1,61 @@
+//! A global allocator applies to the whole test binary, so the counting one
+//! lives in its own integration test instead of the library's unit tests.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use learn_unsafe::spinlock::SpinLock;
+
+// Counts allocations per thread so tests running in parallel don't disturb
+// each other. The const-initialized thread local never allocates itself.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn spinlock_lock_with_does_not_allocate() {
+    let lock = SpinLock::new(Vec::with_capacity(16));
+
+    let before = allocations();
+    let len = lock.lock_with(|v| {
+        v.extend_from_slice(&[1, 2, 3]);
+        v.len()
+    });
+    assert_eq!(allocations(), before);
+    assert_eq!(len, 3);
+
+    // The counter does see allocations made inside the closure.
+    lock.lock_with(|v| v.extend(0..100));
+    assert!(allocations() > before);
+}